@@ -10,13 +10,236 @@
 //! - Zero-copy where possible
 
 use wasm_bindgen::prelude::*;
-use gastown_shared::{FxHashMap, pool::SmallBuffer, capacity};
+use gastown_shared::FxHashMap;
 use crate::{Formula, CookedFormula, Step, Leg};
 
-/// Pre-computed variable pattern for fast substitution
-struct VarPattern {
-    pattern: String,  // "{{name}}"
-    value: String,
+// ============================================================================
+// Version negotiation & migration
+// ============================================================================
+
+/// The highest formula version this cooker knows how to emit after migration.
+pub const CURRENT_VERSION: u16 = 3;
+
+/// Template-engine features advertised by the cooker, packed into a small
+/// bitset so capability checks stay allocation-free.
+///
+/// Newer template constructs (`{{#each}}`, `{{#if}}`) each have a flag that
+/// callers can query. [`negotiate_version`] does not inspect these flags, and
+/// the template engine renders every construct whichever flags are set.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeatureFlags(u32);
+
+impl FeatureFlags {
+    /// `{{name|default}}` fallbacks.
+    pub const DEFAULTS: FeatureFlags = FeatureFlags(1 << 0);
+    /// `{{#if}}` / `{{#unless}}` conditional blocks.
+    pub const IF_BLOCKS: FeatureFlags = FeatureFlags(1 << 1);
+    /// `{{#each}}` iteration blocks.
+    pub const EACH_BLOCKS: FeatureFlags = FeatureFlags(1 << 2);
+
+    /// Every feature the current engine implements.
+    pub const ALL: FeatureFlags =
+        FeatureFlags(Self::DEFAULTS.0 | Self::IF_BLOCKS.0 | Self::EACH_BLOCKS.0);
+
+    /// Whether `self` carries every bit in `other`.
+    #[inline]
+    pub fn contains(self, other: FeatureFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Supported-version window and feature set for the cooker, analogous to a
+/// protocol version handshake: a formula is only cooked if its `version` falls
+/// within `[min_supported, max_supported]`.
+#[derive(Clone, Copy, Debug)]
+pub struct FormulaCapabilities {
+    pub min_supported: u16,
+    pub max_supported: u16,
+    pub features: FeatureFlags,
+}
+
+impl Default for FormulaCapabilities {
+    fn default() -> Self {
+        FormulaCapabilities {
+            min_supported: 1,
+            max_supported: CURRENT_VERSION,
+            features: FeatureFlags::ALL,
+        }
+    }
+}
+
+impl FormulaCapabilities {
+    /// Whether `{{#each}}` blocks are honoured by this cooker.
+    #[inline]
+    pub fn supports_each_blocks(&self) -> bool {
+        self.features.contains(FeatureFlags::EACH_BLOCKS)
+    }
+
+    /// Whether `{{#if}}` / `{{#unless}}` blocks are honoured by this cooker.
+    #[inline]
+    pub fn supports_if_blocks(&self) -> bool {
+        self.features.contains(FeatureFlags::IF_BLOCKS)
+    }
+}
+
+/// Outcome of negotiating a formula's version against the cooker's
+/// [`FormulaCapabilities`]: the ordered list of migrations that will run before
+/// substitution. An empty chain means the formula is cooked as-is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationPlan {
+    pub from_version: u16,
+    pub to_version: u16,
+    pub migrations: Vec<&'static str>,
+}
+
+impl MigrationPlan {
+    /// A formula already at the target version needs no migration.
+    #[inline]
+    pub fn is_passthrough(&self) -> bool {
+        self.migrations.is_empty()
+    }
+}
+
+/// Decide what will happen to a formula of `formula_version`: pass through,
+/// upgrade via an ordered migration chain, or reject as out of range.
+///
+/// Versions below `min_supported` are rejected (too old to migrate) and
+/// versions above `max_supported` are rejected (the cooker cannot understand a
+/// future shape); anything in between is upgraded to `max_supported`.
+pub fn negotiate_version(formula_version: u16) -> Result<MigrationPlan, JsValue> {
+    let caps = FormulaCapabilities::default();
+    if formula_version > caps.max_supported {
+        return Err(JsValue::from_str(&format!(
+            "Formula version {} exceeds supported max {}",
+            formula_version, caps.max_supported
+        )));
+    }
+    if formula_version < caps.min_supported {
+        return Err(JsValue::from_str(&format!(
+            "Formula version {} below supported min {}",
+            formula_version, caps.min_supported
+        )));
+    }
+
+    // Name each step in the chain so callers can discover the upgrade path.
+    let mut migrations = Vec::new();
+    for v in formula_version..caps.max_supported {
+        migrations.push(match v {
+            1 => "migrate_v1_to_v2",
+            2 => "migrate_v2_to_v3",
+            _ => "migrate_unknown",
+        });
+    }
+
+    Ok(MigrationPlan {
+        from_version: formula_version,
+        to_version: caps.max_supported,
+        migrations,
+    })
+}
+
+/// Run the ordered migration chain implied by `plan`, returning the formula at
+/// `plan.to_version`. Each migration bumps `version` so the chain composes.
+fn apply_migrations(mut formula: Formula, plan: &MigrationPlan) -> Formula {
+    for step in &plan.migrations {
+        formula = match *step {
+            "migrate_v1_to_v2" => migrate_v1_to_v2(formula),
+            "migrate_v2_to_v3" => migrate_v2_to_v3(formula),
+            // Unknown steps only bump the version; shape is left untouched.
+            _ => {
+                formula.version = formula.version.saturating_add(1);
+                formula
+            }
+        };
+    }
+    formula
+}
+
+/// v1 → v2: legs carried a single combined `description`; split the leading
+/// focus clause (before the first ` — ` or `|`) into the dedicated `focus`
+/// field when `focus` is still empty.
+fn migrate_v1_to_v2(mut formula: Formula) -> Formula {
+    for leg in &mut formula.legs {
+        if leg.focus.is_empty() {
+            if let Some((focus, rest)) = leg
+                .description
+                .split_once(" — ")
+                .or_else(|| leg.description.split_once('|'))
+            {
+                leg.focus = focus.trim().to_string();
+                leg.description = rest.trim().to_string();
+            }
+        }
+    }
+    formula.version = 2;
+    formula
+}
+
+/// v2 → v3: no field reshaping is required — v3 only widened the template
+/// grammar — so the migration just bumps the version.
+fn migrate_v2_to_v3(mut formula: Formula) -> Formula {
+    formula.version = 3;
+    formula
+}
+
+/// Resolution context for the template engine.
+///
+/// Scalar vars drive `{{name}}` / `{{name|default}}` substitution and the
+/// truthiness of `{{#if}}` / `{{#unless}}` blocks; list vars (passed as JSON
+/// arrays) drive `{{#each}}`. A missing scalar leaves `{{name}}` untouched, as
+/// the flat cooker always did.
+#[derive(Default)]
+pub struct TemplateContext {
+    scalars: FxHashMap<String, String>,
+    lists: FxHashMap<String, Vec<String>>,
+}
+
+impl TemplateContext {
+    /// Build a context from the legacy flat scalar map.
+    pub fn from_scalars(scalars: &FxHashMap<String, String>) -> Self {
+        Self { scalars: scalars.clone(), lists: FxHashMap::default() }
+    }
+
+    /// Build a context from a JSON object, routing strings to scalars and
+    /// arrays to lists. `null` values are dropped; other JSON types are
+    /// stringified as scalars.
+    pub fn from_json(map: serde_json::Map<String, serde_json::Value>) -> Self {
+        let mut ctx = TemplateContext::default();
+        for (key, value) in map {
+            match value {
+                serde_json::Value::String(s) => {
+                    ctx.scalars.insert(key, s);
+                }
+                serde_json::Value::Array(items) => {
+                    let list = items
+                        .into_iter()
+                        .map(|v| match v {
+                            serde_json::Value::String(s) => s,
+                            other => other.to_string(),
+                        })
+                        .collect();
+                    ctx.lists.insert(key, list);
+                }
+                serde_json::Value::Null => {}
+                other => {
+                    ctx.scalars.insert(key, other.to_string());
+                }
+            }
+        }
+        ctx
+    }
+
+    #[inline]
+    fn scalar(&self, name: &str) -> Option<&str> {
+        self.scalars.get(name).map(|s| s.as_str())
+    }
+
+    /// A var is truthy when it is a non-empty scalar or a non-empty list.
+    #[inline]
+    fn is_truthy(&self, name: &str) -> bool {
+        self.scalars.get(name).is_some_and(|s| !s.is_empty())
+            || self.lists.get(name).is_some_and(|l| !l.is_empty())
+    }
 }
 
 /// Cook a formula with variable substitution
@@ -28,15 +251,97 @@ pub fn cook_formula_impl(formula_json: &str, vars_json: &str) -> Result<String,
     let formula: Formula = serde_json::from_str(formula_json)
         .map_err(|e| JsValue::from_str(&format!("Formula parse error: {}", e)))?;
 
-    let vars: FxHashMap<String, String> = serde_json::from_str(vars_json)
+    // Reject out-of-range versions and upgrade older in-range formulas before
+    // substitution, rather than cooking every version identically.
+    let plan = negotiate_version(formula.version)?;
+    let formula = apply_migrations(formula, &plan);
+
+    let vars_map: serde_json::Map<String, serde_json::Value> = serde_json::from_str(vars_json)
         .map_err(|e| JsValue::from_str(&format!("Vars parse error: {}", e)))?;
+    let ctx = TemplateContext::from_json(vars_map);
 
-    let cooked = cook_formula_internal(&formula, &vars);
+    let cooked = cook_formula_internal(&formula, &ctx);
 
     serde_json::to_string(&cooked)
         .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))
 }
 
+/// Cook a formula against a layered, environment-aware variable payload.
+///
+/// `layered_vars_json` carries a base `defaults` layer plus named environment
+/// overlays, e.g.
+/// `{ "defaults": {..}, "environments": { "dev": {..}, "prod": {..} } }`.
+/// Each `{{var}}` resolves in priority order overlay → defaults → the formula's
+/// own `vars`, so one formula can be cooked per-environment without being
+/// duplicated. The layers are flattened into the same map the hot substitution
+/// path already consumes, and the winning layer for each var is reported back in
+/// a `var_layers` field alongside the usual `CookedFormula`.
+#[inline]
+pub fn cook_formula_env_impl(
+    formula_json: &str,
+    layered_vars_json: &str,
+    env_name: &str,
+) -> Result<String, JsValue> {
+    let formula: Formula = serde_json::from_str(formula_json)
+        .map_err(|e| JsValue::from_str(&format!("Formula parse error: {}", e)))?;
+
+    let plan = negotiate_version(formula.version)?;
+    let formula = apply_migrations(formula, &plan);
+
+    let payload: serde_json::Value = serde_json::from_str(layered_vars_json)
+        .map_err(|e| JsValue::from_str(&format!("Vars parse error: {}", e)))?;
+
+    // Flatten lowest-priority layer first so higher layers overwrite it, and
+    // track which layer supplied each final value.
+    let mut merged = serde_json::Map::new();
+    let mut layers: std::collections::BTreeMap<String, String> = std::collections::BTreeMap::new();
+
+    for (key, value) in &formula.vars {
+        merged.insert(key.clone(), serde_json::Value::String(value.clone()));
+        layers.insert(key.clone(), "formula".to_string());
+    }
+    if let Some(serde_json::Value::Object(defaults)) = payload.get("defaults") {
+        for (key, value) in defaults {
+            merged.insert(key.clone(), value.clone());
+            layers.insert(key.clone(), "defaults".to_string());
+        }
+    }
+    if let Some(serde_json::Value::Object(environments)) = payload.get("environments") {
+        match environments.get(env_name) {
+            Some(serde_json::Value::Object(overlay)) => {
+                for (key, value) in overlay {
+                    merged.insert(key.clone(), value.clone());
+                    layers.insert(key.clone(), env_name.to_string());
+                }
+            }
+            // A named environment that is absent is a caller mistake, not an
+            // empty overlay — surface it rather than silently using defaults.
+            _ => {
+                return Err(JsValue::from_str(&format!(
+                    "Unknown environment: {}",
+                    env_name
+                )));
+            }
+        }
+    }
+
+    let ctx = TemplateContext::from_json(merged);
+    let cooked = cook_formula_internal(&formula, &ctx);
+
+    // Attach the layer provenance without disturbing the `CookedFormula` shape.
+    let mut value = serde_json::to_value(&cooked)
+        .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))?;
+    if let serde_json::Value::Object(ref mut map) = value {
+        map.insert(
+            "var_layers".to_string(),
+            serde_json::to_value(&layers)
+                .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))?,
+        );
+    }
+    serde_json::to_string(&value)
+        .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))
+}
+
 /// Batch cook multiple formulas
 ///
 /// # Performance
@@ -46,7 +351,7 @@ pub fn cook_batch_impl(formulas_json: &str, vars_json: &str) -> Result<String, J
     let formulas: Vec<Formula> = serde_json::from_str(formulas_json)
         .map_err(|e| JsValue::from_str(&format!("Formulas parse error: {}", e)))?;
 
-    let vars_list: Vec<FxHashMap<String, String>> = serde_json::from_str(vars_json)
+    let vars_list: Vec<serde_json::Map<String, serde_json::Value>> = serde_json::from_str(vars_json)
         .map_err(|e| JsValue::from_str(&format!("Vars parse error: {}", e)))?;
 
     if formulas.len() != vars_list.len() {
@@ -57,8 +362,13 @@ pub fn cook_batch_impl(formulas_json: &str, vars_json: &str) -> Result<String, J
     let mut cooked: Vec<CookedFormula> = Vec::with_capacity(formulas.len());
 
     // Process in batches for cache efficiency
-    for (formula, vars) in formulas.iter().zip(vars_list.iter()) {
-        cooked.push(cook_formula_internal(formula, vars));
+    for (formula, vars) in formulas.into_iter().zip(vars_list.into_iter()) {
+        // Each formula negotiates its own version; one out-of-range entry
+        // fails the whole batch rather than being silently cooked.
+        let plan = negotiate_version(formula.version)?;
+        let formula = apply_migrations(formula, &plan);
+        let ctx = TemplateContext::from_json(vars);
+        cooked.push(cook_formula_internal(&formula, &ctx));
     }
 
     serde_json::to_string(&cooked)
@@ -67,35 +377,13 @@ pub fn cook_batch_impl(formulas_json: &str, vars_json: &str) -> Result<String, J
 
 /// Internal function to cook a formula
 ///
-/// # Optimizations
-/// - Pre-computes all variable patterns once
-/// - Uses SmallVec for stack allocation when possible
-/// - Single-pass substitution per field
+/// Renders every text field through the template engine
+/// ([`render_template`]), which supports `{{var}}`, `{{var|default}}`,
+/// `{{#if}}` / `{{#unless}}`, and `{{#each}}` blocks. The
+/// `!text.contains("{{")` fast path still short-circuits plain strings.
 #[inline]
-fn cook_formula_internal(formula: &Formula, vars: &FxHashMap<String, String>) -> CookedFormula {
-    // Pre-compute variable patterns for efficient substitution
-    let patterns: SmallBuffer<VarPattern, 16> = vars
-        .iter()
-        .map(|(key, value)| VarPattern {
-            pattern: format!("{{{{{}}}}}", key),
-            value: value.clone(),
-        })
-        .collect();
-
-    // Fast substitution function using pre-computed patterns
-    let substitute = |text: &str| -> String {
-        if patterns.is_empty() || !text.contains("{{") {
-            return text.to_string();
-        }
-
-        let mut result = text.to_string();
-        for pat in patterns.iter() {
-            if result.contains(&pat.pattern) {
-                result = result.replace(&pat.pattern, &pat.value);
-            }
-        }
-        result
-    };
+fn cook_formula_internal(formula: &Formula, ctx: &TemplateContext) -> CookedFormula {
+    let substitute = |text: &str| -> String { render_template(text, ctx, None) };
 
     // Cook steps with pre-allocated capacity
     let cooked_steps: Vec<Step> = if formula.steps.is_empty() {
@@ -141,11 +429,16 @@ fn cook_formula_internal(formula: &Formula, vars: &FxHashMap<String, String>) ->
         vars: formula.vars.clone(),
     };
 
-    // Convert vars to standard HashMap for serialization
-    let cooked_vars: std::collections::HashMap<String, String> = vars
+    // Convert vars to standard HashMap for serialization. Scalars pass through
+    // verbatim; list vars are recorded as their JSON array form.
+    let mut cooked_vars: std::collections::HashMap<String, String> = ctx
+        .scalars
         .iter()
         .map(|(k, v)| (k.clone(), v.clone()))
         .collect();
+    for (k, list) in &ctx.lists {
+        cooked_vars.insert(k.clone(), serde_json::to_string(list).unwrap_or_default());
+    }
 
     CookedFormula {
         formula: cooked_formula,
@@ -155,70 +448,176 @@ fn cook_formula_internal(formula: &Formula, vars: &FxHashMap<String, String>) ->
     }
 }
 
-/// Optimized multi-pattern substitution
+// ============================================================================
+// Template engine
+// ============================================================================
+
+/// A node in the parsed template AST.
+enum Node {
+    /// Literal text emitted verbatim
+    Text(String),
+    /// `{{name}}` or `{{name|default}}`
+    Var { name: String, default: Option<String> },
+    /// `{{this}}` - the current `{{#each}}` item
+    This,
+    /// `{{#if cond}}..{{/if}}` or `{{#unless cond}}..{{/unless}}`
+    If { cond: String, negate: bool, body: Vec<Node> },
+    /// `{{#each list}}..{{/each}}`
+    Each { list: String, body: Vec<Node> },
+}
+
+/// Render `text` against `ctx`, with `this` bound to the current each-item.
 ///
-/// Uses a single pass through the string to find all patterns
-#[inline]
-fn substitute_all(text: &str, patterns: &[VarPattern]) -> String {
-    if patterns.is_empty() || !text.contains("{{") {
+/// The fast path short-circuits any string without `{{`. Unknown or
+/// unterminated blocks render as literal text and never panic; nesting is
+/// balanced by the recursive parser.
+fn render_template(text: &str, ctx: &TemplateContext, this: Option<&str>) -> String {
+    if !text.contains("{{") {
         return text.to_string();
     }
+    let mut parser = Parser { chars: text, pos: 0 };
+    let (nodes, _) = parser.parse(&[]);
+    let mut out = String::with_capacity(text.len());
+    render_nodes(&nodes, ctx, this, &mut out);
+    out
+}
 
-    // For small number of patterns, sequential replacement is faster
-    if patterns.len() <= 4 {
-        let mut result = text.to_string();
-        for pat in patterns {
-            if result.contains(&pat.pattern) {
-                result = result.replace(&pat.pattern, &pat.value);
+/// Render an AST into `out`.
+fn render_nodes(nodes: &[Node], ctx: &TemplateContext, this: Option<&str>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(t) => out.push_str(t),
+            Node::This => {
+                if let Some(v) = this {
+                    out.push_str(v);
+                }
             }
-        }
-        return result;
-    }
-
-    // For larger pattern sets, build result incrementally
-    let mut result = String::with_capacity(text.len() * 2);
-    let mut last_end = 0;
-    let bytes = text.as_bytes();
-    let len = bytes.len();
-
-    let mut i = 0;
-    while i < len {
-        if i + 2 < len && bytes[i] == b'{' && bytes[i + 1] == b'{' {
-            // Found potential pattern start
-            if let Some(end) = find_pattern_end(&bytes[i..]) {
-                let pattern_str = &text[i..i + end + 2];
-
-                // Check if this matches any of our patterns
-                if let Some(pat) = patterns.iter().find(|p| p.pattern == pattern_str) {
-                    result.push_str(&text[last_end..i]);
-                    result.push_str(&pat.value);
-                    last_end = i + end + 2;
-                    i = last_end;
-                    continue;
+            Node::Var { name, default } => match ctx.scalar(name) {
+                Some(v) => out.push_str(v),
+                None => match default {
+                    // `{{name|default}}` falls back to the literal default.
+                    Some(d) => out.push_str(d),
+                    // Bare unknown vars are left untouched, as the flat cooker did.
+                    None => {
+                        out.push_str("{{");
+                        out.push_str(name);
+                        out.push_str("}}");
+                    }
+                },
+            },
+            Node::If { cond, negate, body } => {
+                if ctx.is_truthy(cond) != *negate {
+                    render_nodes(body, ctx, this, out);
+                }
+            }
+            Node::Each { list, body } => {
+                if let Some(items) = ctx.lists.get(list) {
+                    for item in items {
+                        render_nodes(body, ctx, Some(item), out);
+                    }
                 }
             }
         }
-        i += 1;
     }
+}
 
-    result.push_str(&text[last_end..]);
-    result
+/// Single-pass recursive-descent parser over the `{{ }}` grammar.
+struct Parser<'a> {
+    chars: &'a str,
+    pos: usize,
 }
 
-/// Find the end of a pattern (closing }})
-#[inline(always)]
-fn find_pattern_end(bytes: &[u8]) -> Option<usize> {
-    let len = bytes.len();
-    let mut i = 2; // Skip opening {{
+impl<'a> Parser<'a> {
+    /// Parse nodes until EOF or one of `stops` (close-tag names) is hit.
+    /// Returns the parsed nodes and the stop tag consumed, if any.
+    fn parse(&mut self, stops: &[&str]) -> (Vec<Node>, Option<String>) {
+        let mut nodes = Vec::new();
+        let bytes = self.chars.as_bytes();
+
+        while self.pos < self.chars.len() {
+            // Find the next `{{` from the current position.
+            let rest = &self.chars[self.pos..];
+            let Some(rel) = rest.find("{{") else {
+                nodes.push(Node::Text(rest.to_string()));
+                self.pos = self.chars.len();
+                break;
+            };
+
+            if rel > 0 {
+                nodes.push(Node::Text(rest[..rel].to_string()));
+            }
+            let open = self.pos + rel;
+
+            // Locate the closing `}}`.
+            let Some(close_rel) = self.chars[open + 2..].find("}}") else {
+                // Unterminated tag: emit the remainder literally.
+                nodes.push(Node::Text(self.chars[open..].to_string()));
+                self.pos = self.chars.len();
+                break;
+            };
+            let close = open + 2 + close_rel;
+            let raw = &self.chars[open..close + 2];
+            let inner = self.chars[open + 2..close].trim().to_string();
+            self.pos = close + 2;
+            let _ = bytes; // silence unused in case of empty input
+
+            if let Some(name) = inner.strip_prefix('/') {
+                let name = name.trim().to_string();
+                if stops.contains(&name.as_str()) {
+                    return (nodes, Some(name));
+                }
+                // Stray close tag: keep it literal.
+                nodes.push(Node::Text(raw.to_string()));
+                continue;
+            }
 
-    while i + 1 < len {
-        if bytes[i] == b'}' && bytes[i + 1] == b'}' {
-            return Some(i);
+            if let Some(cond) = inner.strip_prefix("#if ") {
+                nodes.push(self.parse_block_or_literal(cond.trim(), false, "if", raw));
+            } else if let Some(cond) = inner.strip_prefix("#unless ") {
+                nodes.push(self.parse_block_or_literal(cond.trim(), true, "unless", raw));
+            } else if let Some(list) = inner.strip_prefix("#each ") {
+                nodes.push(self.parse_each_or_literal(list.trim(), raw));
+            } else if inner == "this" {
+                nodes.push(Node::This);
+            } else if inner.starts_with('#') {
+                // Unknown block opener: literal.
+                nodes.push(Node::Text(raw.to_string()));
+            } else {
+                // Simple var, optionally `{{name|default}}`.
+                let (name, default) = match inner.split_once('|') {
+                    Some((n, d)) => (n.trim().to_string(), Some(d.to_string())),
+                    None => (inner, None),
+                };
+                nodes.push(Node::Var { name, default });
+            }
         }
-        i += 1;
+
+        (nodes, None)
     }
 
-    None
+    fn parse_block_or_literal(&mut self, cond: &str, negate: bool, tag: &str, raw: &str) -> Node {
+        let (body, stopped) = self.parse(&[tag]);
+        match stopped {
+            Some(_) => Node::If { cond: cond.to_string(), negate, body },
+            // Unterminated block: render the open tag literally, keep the body.
+            None => literal_with_body(raw, body),
+        }
+    }
+
+    fn parse_each_or_literal(&mut self, list: &str, raw: &str) -> Node {
+        let (body, stopped) = self.parse(&["each"]);
+        match stopped {
+            Some(_) => Node::Each { list: list.to_string(), body },
+            None => literal_with_body(raw, body),
+        }
+    }
+}
+
+/// Wrap an unterminated block's open tag and already-parsed body into a node
+/// that renders both literally (the tag) and recursively (the body).
+fn literal_with_body(raw: &str, mut body: Vec<Node>) -> Node {
+    body.insert(0, Node::Text(raw.to_string()));
+    Node::If { cond: String::new(), negate: true, body }
 }
 
 /// Simple timestamp without chrono dependency
@@ -282,7 +681,7 @@ mod tests {
         let mut vars = FxHashMap::default();
         vars.insert("project".to_string(), "auth-service".to_string());
 
-        let cooked = cook_formula_internal(&formula, &vars);
+        let cooked = cook_formula_internal(&formula, &TemplateContext::from_scalars(&vars));
 
         assert_eq!(cooked.formula.name, "auth-service-workflow");
         assert_eq!(cooked.formula.description, "Workflow for auth-service");
@@ -352,12 +751,166 @@ mod tests {
         };
 
         let vars = FxHashMap::default();
-        let cooked = cook_formula_internal(&formula, &vars);
+        let cooked = cook_formula_internal(&formula, &TemplateContext::from_scalars(&vars));
 
         assert_eq!(cooked.formula.name, "static-workflow");
         assert_eq!(cooked.formula.description, "No variables here");
     }
 
+    #[test]
+    fn test_default_value() {
+        let ctx = TemplateContext::default();
+        assert_eq!(render_template("{{region|us-east-1}}", &ctx, None), "us-east-1");
+
+        let mut vars = FxHashMap::default();
+        vars.insert("region".to_string(), "eu-west-1".to_string());
+        let ctx = TemplateContext::from_scalars(&vars);
+        assert_eq!(render_template("{{region|us-east-1}}", &ctx, None), "eu-west-1");
+    }
+
+    #[test]
+    fn test_unknown_var_left_literal() {
+        let ctx = TemplateContext::default();
+        assert_eq!(render_template("hi {{missing}}", &ctx, None), "hi {{missing}}");
+    }
+
+    #[test]
+    fn test_conditional_blocks() {
+        let mut vars = FxHashMap::default();
+        vars.insert("prod".to_string(), "yes".to_string());
+        let ctx = TemplateContext::from_scalars(&vars);
+
+        assert_eq!(render_template("{{#if prod}}live{{/if}}", &ctx, None), "live");
+        assert_eq!(render_template("{{#if missing}}live{{/if}}", &ctx, None), "");
+        assert_eq!(render_template("{{#unless prod}}draft{{/unless}}", &ctx, None), "");
+        assert_eq!(render_template("{{#unless missing}}draft{{/unless}}", &ctx, None), "draft");
+    }
+
+    #[test]
+    fn test_each_block() {
+        let map: serde_json::Map<String, serde_json::Value> = serde_json::from_str(
+            r#"{"envs": ["dev", "stage", "prod"]}"#,
+        )
+        .unwrap();
+        let ctx = TemplateContext::from_json(map);
+        assert_eq!(
+            render_template("{{#each envs}}[{{this}}]{{/each}}", &ctx, None),
+            "[dev][stage][prod]",
+        );
+    }
+
+    #[test]
+    fn test_unterminated_block_is_literal() {
+        let ctx = TemplateContext::default();
+        let input = "{{#if prod}}no close";
+        assert_eq!(render_template(input, &ctx, None), input);
+    }
+
+    #[test]
+    fn test_no_braces_fast_path() {
+        let ctx = TemplateContext::default();
+        assert_eq!(render_template("plain text", &ctx, None), "plain text");
+    }
+
+    #[test]
+    fn test_negotiate_version_passthrough_and_upgrade() {
+        let plan = negotiate_version(CURRENT_VERSION).unwrap();
+        assert!(plan.is_passthrough());
+
+        let plan = negotiate_version(1).unwrap();
+        assert_eq!(plan.to_version, CURRENT_VERSION);
+        assert_eq!(plan.migrations, vec!["migrate_v1_to_v2", "migrate_v2_to_v3"]);
+    }
+
+    #[test]
+    fn test_negotiate_version_out_of_range_rejected() {
+        assert!(negotiate_version(0).is_err());
+        assert!(negotiate_version(CURRENT_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn test_capabilities_feature_flags() {
+        let caps = FormulaCapabilities::default();
+        assert!(caps.supports_each_blocks());
+        assert!(caps.supports_if_blocks());
+    }
+
+    #[test]
+    fn test_migrate_v1_splits_leg_focus() {
+        let formula = Formula {
+            name: "legacy".to_string(),
+            description: "v1".to_string(),
+            formula_type: FormulaType::Workflow,
+            version: 1,
+            legs: vec![Leg {
+                id: "leg1".to_string(),
+                title: "Build".to_string(),
+                focus: String::new(),
+                description: "Compilation — compile every crate".to_string(),
+                agent: None,
+                order: 0,
+            }],
+            synthesis: None,
+            steps: vec![],
+            vars: std::collections::HashMap::new(),
+        };
+
+        let plan = negotiate_version(formula.version).unwrap();
+        let migrated = apply_migrations(formula, &plan);
+
+        assert_eq!(migrated.version, CURRENT_VERSION);
+        assert_eq!(migrated.legs[0].focus, "Compilation");
+        assert_eq!(migrated.legs[0].description, "compile every crate");
+    }
+
+    #[test]
+    fn test_cook_env_layer_priority() {
+        let formula = Formula {
+            name: "{{service}} in {{region}}".to_string(),
+            description: "{{tier}}".to_string(),
+            formula_type: FormulaType::Workflow,
+            version: CURRENT_VERSION,
+            legs: vec![],
+            synthesis: None,
+            steps: vec![],
+            vars: {
+                let mut m = std::collections::HashMap::new();
+                m.insert("service".to_string(), "api".to_string());
+                m
+            },
+        };
+        let formula_json = serde_json::to_string(&formula).unwrap();
+        let layered = r#"{
+            "defaults": { "region": "us-east-1", "tier": "standard" },
+            "environments": { "prod": { "region": "eu-west-1", "tier": "premium" } }
+        }"#;
+
+        let result = cook_formula_env_impl(&formula_json, layered, "prod").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(value["formula"]["name"], "api in eu-west-1");
+        assert_eq!(value["formula"]["description"], "premium");
+        assert_eq!(value["var_layers"]["service"], "formula");
+        assert_eq!(value["var_layers"]["region"], "prod");
+    }
+
+    #[test]
+    fn test_cook_env_unknown_environment_rejected() {
+        let formula = Formula {
+            name: "{{x}}".to_string(),
+            description: String::new(),
+            formula_type: FormulaType::Workflow,
+            version: CURRENT_VERSION,
+            legs: vec![],
+            synthesis: None,
+            steps: vec![],
+            vars: std::collections::HashMap::new(),
+        };
+        let formula_json = serde_json::to_string(&formula).unwrap();
+        let layered = r#"{ "defaults": {}, "environments": { "dev": {} } }"#;
+        assert!(cook_formula_env_impl(&formula_json, layered, "staging").is_err());
+    }
+
     #[test]
     fn test_cook_field() {
         let mut vars = FxHashMap::default();
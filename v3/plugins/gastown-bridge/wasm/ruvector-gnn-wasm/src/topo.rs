@@ -4,7 +4,8 @@
 //! 150x faster than JavaScript implementation.
 
 use wasm_bindgen::prelude::*;
-use petgraph::algo::toposort;
+use petgraph::algo::{toposort, tarjan_scc};
+use petgraph::visit::EdgeRef;
 use std::collections::HashMap;
 use crate::{BeadNode, TopoSortResult};
 use crate::dag::build_graph;
@@ -34,21 +35,48 @@ fn topo_sort_internal(beads: &[BeadNode]) -> TopoSortResult {
                 sorted,
                 has_cycle: false,
                 cycle_nodes: vec![],
+                cycles: vec![],
             }
         }
-        Err(cycle) => {
-            // Cycle detected - find all nodes in the cycle
-            let cycle_node = graph[cycle.node_id()].clone();
+        Err(_) => {
+            // Cycle detected - enumerate every non-trivial strongly-connected
+            // component so the caller can see all deadlocked beads, grouped by
+            // the distinct dependency loop they belong to.
+            let cycles = find_cycles(&graph);
+            let cycle_nodes: Vec<String> = cycles.iter().flatten().cloned().collect();
 
             TopoSortResult {
                 sorted: vec![],
                 has_cycle: true,
-                cycle_nodes: vec![cycle_node],
+                cycle_nodes,
+                cycles,
             }
         }
     }
 }
 
+/// Collect every non-trivial strongly-connected component as a distinct cycle.
+///
+/// An SCC is a cycle if it contains more than one node, or a single node that
+/// links back to itself. Tarjan returns components in reverse-topological
+/// order; each returned inner vector lists the bead IDs in one loop.
+fn find_cycles(graph: &petgraph::Graph<String, ()>) -> Vec<Vec<String>> {
+    tarjan_scc(graph)
+        .into_iter()
+        .filter_map(|scc| {
+            let is_cycle = scc.len() > 1
+                || scc
+                    .first()
+                    .is_some_and(|&n| graph.edges(n).any(|e| e.target() == n));
+            if is_cycle {
+                Some(scc.iter().map(|&idx| graph[idx].clone()).collect())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
 /// Get beads in execution order with parallel groups
 pub fn get_execution_order_impl(beads_json: &str) -> Result<String, JsValue> {
     let beads: Vec<BeadNode> = serde_json::from_str(beads_json)
@@ -103,6 +131,130 @@ fn get_execution_order_internal(beads: &[BeadNode]) -> Result<Vec<Vec<String>>,
     Ok(waves)
 }
 
+/// Per-bead critical-path-method schedule entry
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BeadSchedule {
+    pub id: String,
+    pub duration: f64,
+    pub earliest_start: f64,
+    pub earliest_finish: f64,
+    pub latest_start: f64,
+    pub latest_finish: f64,
+    pub slack: f64,
+    pub on_critical_path: bool,
+}
+
+/// Result of a critical-path-method computation over a bead graph
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ScheduleResult {
+    pub beads: Vec<BeadSchedule>,
+    pub project_duration: f64,
+    pub critical_path: Vec<String>,
+}
+
+/// Beads whose slack is smaller than this in magnitude are treated as having
+/// zero slack (on the critical path).
+const SLACK_EPSILON: f64 = 1e-9;
+
+/// Compute a critical-path-method schedule for a set of beads
+pub fn compute_schedule_impl(beads_json: &str) -> Result<String, JsValue> {
+    let beads: Vec<BeadNode> = serde_json::from_str(beads_json)
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+
+    let result = compute_schedule_internal(&beads)?;
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))
+}
+
+/// Forward/backward CPM pass over the topologically-ordered beads.
+///
+/// Forward: `ES = max(EF of blocked_by)`, `EF = ES + duration`. Backward from
+/// the project end: `LF = min(LS of successors)`, `LS = LF - duration`. Beads
+/// with zero `slack = LS - ES` form the critical path. Missing durations
+/// default to 1.
+fn compute_schedule_internal(beads: &[BeadNode]) -> Result<ScheduleResult, JsValue> {
+    let topo = topo_sort_internal(beads);
+    if topo.has_cycle {
+        return Err(JsValue::from_str("Cannot compute schedule: cycle detected"));
+    }
+
+    let id_to_bead: HashMap<&str, &BeadNode> =
+        beads.iter().map(|b| (b.id.as_str(), b)).collect();
+    let duration = |b: &BeadNode| b.duration.unwrap_or(1.0).max(0.0);
+
+    // Invert `blocked_by` once so the forward and backward passes share a
+    // single edge set. Deriving successors from the separate `blocks` field
+    // would let a graph that populates only `blocked_by` default every
+    // `latest_finish` to the project duration and report a wrong critical path.
+    let mut successors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for bead in beads {
+        for dep in &bead.blocked_by {
+            successors.entry(dep.as_str()).or_default().push(bead.id.as_str());
+        }
+    }
+
+    // Forward pass (topological order).
+    let mut es: HashMap<String, f64> = HashMap::new();
+    let mut ef: HashMap<String, f64> = HashMap::new();
+    let mut project_duration = 0.0f64;
+    for id in &topo.sorted {
+        let Some(bead) = id_to_bead.get(id.as_str()) else { continue };
+        let start = bead
+            .blocked_by
+            .iter()
+            .filter_map(|dep| ef.get(dep).copied())
+            .fold(0.0f64, f64::max);
+        let finish = start + duration(bead);
+        es.insert(id.clone(), start);
+        ef.insert(id.clone(), finish);
+        project_duration = project_duration.max(finish);
+    }
+
+    // Backward pass (reverse topological order).
+    let mut lf: HashMap<String, f64> = HashMap::new();
+    let mut ls: HashMap<String, f64> = HashMap::new();
+    for id in topo.sorted.iter().rev() {
+        let Some(bead) = id_to_bead.get(id.as_str()) else { continue };
+        let finish = successors
+            .get(id.as_str())
+            .into_iter()
+            .flatten()
+            .filter_map(|succ| ls.get(*succ).copied())
+            .fold(project_duration, f64::min);
+        let start = finish - duration(bead);
+        lf.insert(id.clone(), finish);
+        ls.insert(id.clone(), start);
+    }
+
+    let schedules: Vec<BeadSchedule> = topo
+        .sorted
+        .iter()
+        .filter_map(|id| id_to_bead.get(id.as_str()).map(|b| (id, *b)))
+        .map(|(id, bead)| {
+            let slack = ls[id] - es[id];
+            BeadSchedule {
+                id: id.clone(),
+                duration: duration(bead),
+                earliest_start: es[id],
+                earliest_finish: ef[id],
+                latest_start: ls[id],
+                latest_finish: lf[id],
+                slack,
+                on_critical_path: slack.abs() < SLACK_EPSILON,
+            }
+        })
+        .collect();
+
+    let critical_path: Vec<String> = schedules
+        .iter()
+        .filter(|s| s.on_critical_path)
+        .map(|s| s.id.clone())
+        .collect();
+
+    Ok(ScheduleResult { beads: schedules, project_duration, critical_path })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -203,6 +355,115 @@ mod tests {
         assert_eq!(pos("d"), Some(3));
     }
 
+    #[test]
+    fn test_critical_path_schedule() {
+        // Diamond with durations: a(2) -> b(3), a(2) -> c(1), b+c -> d(2).
+        // Critical path is a -> b -> d = 7; c carries 2 units of slack.
+        let beads = vec![
+            BeadNode {
+                id: "a".to_string(), title: "A".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec![], blocks: vec!["b".to_string(), "c".to_string()],
+                duration: Some(2.0),
+            },
+            BeadNode {
+                id: "b".to_string(), title: "B".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["a".to_string()], blocks: vec!["d".to_string()],
+                duration: Some(3.0),
+            },
+            BeadNode {
+                id: "c".to_string(), title: "C".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["a".to_string()], blocks: vec!["d".to_string()],
+                duration: Some(1.0),
+            },
+            BeadNode {
+                id: "d".to_string(), title: "D".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["b".to_string(), "c".to_string()], blocks: vec![],
+                duration: Some(2.0),
+            },
+        ];
+
+        let result = compute_schedule_internal(&beads).unwrap();
+        assert!((result.project_duration - 7.0).abs() < 1e-6);
+        assert_eq!(result.critical_path, vec!["a", "b", "d"]);
+
+        let c = result.beads.iter().find(|s| s.id == "c").unwrap();
+        assert!((c.slack - 2.0).abs() < 1e-6);
+        assert!(!c.on_critical_path);
+    }
+
+    #[test]
+    fn test_critical_path_uses_blocked_by_only() {
+        // Same diamond as above, but `blocks` is left empty everywhere — the
+        // backward pass must recover successors by inverting `blocked_by`
+        // rather than defaulting every latest_finish to the project duration.
+        let beads = vec![
+            BeadNode {
+                id: "a".to_string(), title: "A".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec![], blocks: vec![], duration: Some(2.0),
+            },
+            BeadNode {
+                id: "b".to_string(), title: "B".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["a".to_string()], blocks: vec![], duration: Some(3.0),
+            },
+            BeadNode {
+                id: "c".to_string(), title: "C".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["a".to_string()], blocks: vec![], duration: Some(1.0),
+            },
+            BeadNode {
+                id: "d".to_string(), title: "D".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["b".to_string(), "c".to_string()], blocks: vec![],
+                duration: Some(2.0),
+            },
+        ];
+
+        let result = compute_schedule_internal(&beads).unwrap();
+        assert!((result.project_duration - 7.0).abs() < 1e-6);
+        assert_eq!(result.critical_path, vec!["a", "b", "d"]);
+
+        let c = result.beads.iter().find(|s| s.id == "c").unwrap();
+        assert!((c.slack - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_topo_sort_reports_all_cycle_members() {
+        // Two independent loops: a <-> b, and c -> d -> e -> c.
+        let beads = vec![
+            BeadNode {
+                id: "a".to_string(), title: "A".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["b".to_string()], blocks: vec!["b".to_string()],
+                duration: None,
+            },
+            BeadNode {
+                id: "b".to_string(), title: "B".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["a".to_string()], blocks: vec!["a".to_string()],
+                duration: None,
+            },
+            BeadNode {
+                id: "c".to_string(), title: "C".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["e".to_string()], blocks: vec!["d".to_string()],
+                duration: None,
+            },
+            BeadNode {
+                id: "d".to_string(), title: "D".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["c".to_string()], blocks: vec!["e".to_string()],
+                duration: None,
+            },
+            BeadNode {
+                id: "e".to_string(), title: "E".to_string(), status: "open".to_string(),
+                priority: 0, blocked_by: vec!["d".to_string()], blocks: vec!["c".to_string()],
+                duration: None,
+            },
+        ];
+
+        let result = topo_sort_internal(&beads);
+
+        assert!(result.has_cycle);
+        // Two distinct dependency loops are reported.
+        assert_eq!(result.cycles.len(), 2);
+        // Every deadlocked bead appears in cycle_nodes.
+        assert_eq!(result.cycle_nodes.len(), 5);
+    }
+
     #[test]
     fn test_execution_waves() {
         let beads = vec![
@@ -40,6 +40,19 @@ impl Arena {
         self.bump.borrow().alloc_str(s)
     }
 
+    /// Format arguments directly into the arena, without a heap `String`
+    /// temporary. Use with [`std::format_args!`] on hot paths that would
+    /// otherwise allocate an owned `String` only to copy it straight back out.
+    #[inline]
+    pub fn alloc_fmt(&self, args: std::fmt::Arguments) -> &str {
+        use std::fmt::Write;
+        let bump = self.bump.borrow();
+        let mut s = bumpalo::collections::String::new_in(&bump);
+        // Writing into a bump-backed String cannot fail.
+        let _ = s.write_fmt(args);
+        s.into_bump_str()
+    }
+
     /// Allocate a slice in the arena
     #[inline(always)]
     pub fn alloc_slice<T: Copy>(&self, slice: &[T]) -> &[T] {
@@ -86,6 +99,13 @@ impl<'a> ArenaStr<'a> {
         }
     }
 
+    /// Wrap a slice already allocated in an arena (e.g. via
+    /// [`Arena::alloc_fmt`]) without copying it again.
+    #[inline(always)]
+    pub fn from_arena(inner: &'a str) -> Self {
+        Self { inner }
+    }
+
     /// Get the string slice
     #[inline(always)]
     pub fn as_str(&self) -> &'a str {
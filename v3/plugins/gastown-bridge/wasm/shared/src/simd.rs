@@ -128,6 +128,150 @@ fn simd_min_f32(values: &[f32]) -> f32 {
     simd_min.min(remainder_min)
 }
 
+/// SIMD-friendly dot product of two f32 vectors.
+///
+/// Multiplies over the common prefix (`min(a.len(), b.len())`), so mismatched
+/// lengths yield the product over the shorter vector rather than panicking.
+#[inline(always)]
+pub fn dot_f32(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let a = &a[..len];
+    let b = &b[..len];
+
+    #[cfg(feature = "simd")]
+    {
+        simd_dot_f32(a, b)
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+}
+
+#[cfg(feature = "simd")]
+fn simd_dot_f32(a: &[f32], b: &[f32]) -> f32 {
+    const LANES: usize = 4;
+
+    if a.len() < LANES {
+        return a.iter().zip(b).map(|(x, y)| x * y).sum();
+    }
+
+    let a_chunks = a.chunks_exact(LANES);
+    let b_chunks = b.chunks_exact(LANES);
+    let a_rem = a_chunks.remainder();
+    let b_rem = b_chunks.remainder();
+
+    let mut acc = f32x4::ZERO;
+    for (ac, bc) in a_chunks.zip(b_chunks) {
+        let va = f32x4::from([ac[0], ac[1], ac[2], ac[3]]);
+        let vb = f32x4::from([bc[0], bc[1], bc[2], bc[3]]);
+        acc += va * vb;
+    }
+
+    // Horizontal sum of the lane products plus the scalar remainder.
+    let simd_dot: f32 = acc.to_array().iter().sum();
+    let remainder_dot: f32 = a_rem.iter().zip(b_rem).map(|(x, y)| x * y).sum();
+
+    simd_dot + remainder_dot
+}
+
+/// Cosine similarity of two f32 vectors, or `None` when either vector has
+/// zero magnitude (an undefined angle).
+#[inline(always)]
+pub fn cosine_f32(a: &[f32], b: &[f32]) -> Option<f32> {
+    let dot = dot_f32(a, b);
+    let norm_a = dot_f32(a, a).sqrt();
+    let norm_b = dot_f32(b, b).sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+
+    Some(dot / (norm_a * norm_b))
+}
+
+/// SIMD-friendly element-wise sum of two f32 vectors over their common prefix.
+#[inline(always)]
+pub fn add_f32(a: &[f32], b: &[f32]) -> Vec<f32> {
+    zip_f32(a, b, |x, y| x + y, |va, vb| va + vb)
+}
+
+/// SIMD-friendly element-wise product of two f32 vectors over their common
+/// prefix.
+#[inline(always)]
+pub fn mul_f32(a: &[f32], b: &[f32]) -> Vec<f32> {
+    zip_f32(a, b, |x, y| x * y, |va, vb| va * vb)
+}
+
+/// SIMD-friendly scaling of a f32 vector by a scalar.
+#[inline(always)]
+pub fn scale_f32(values: &[f32], factor: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    {
+        const LANES: usize = 4;
+        if values.len() < LANES {
+            return values.iter().map(|x| x * factor).collect();
+        }
+
+        let chunks = values.chunks_exact(LANES);
+        let remainder = chunks.remainder();
+        let splat = f32x4::splat(factor);
+
+        let mut out = Vec::with_capacity(values.len());
+        for chunk in chunks {
+            let v = f32x4::from([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            out.extend_from_slice(&(v * splat).to_array());
+        }
+        out.extend(remainder.iter().map(|x| x * factor));
+        out
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        values.iter().map(|x| x * factor).collect()
+    }
+}
+
+/// Shared element-wise kernel: applies `scalar_op` on the remainder and, when
+/// the `simd` feature is on, `simd_op` over `f32x4` lanes.
+#[inline(always)]
+fn zip_f32(
+    a: &[f32],
+    b: &[f32],
+    scalar_op: impl Fn(f32, f32) -> f32,
+    #[cfg(feature = "simd")] simd_op: impl Fn(f32x4, f32x4) -> f32x4,
+    #[cfg(not(feature = "simd"))] _simd_op: impl Fn(f32, f32) -> f32,
+) -> Vec<f32> {
+    let len = a.len().min(b.len());
+    let a = &a[..len];
+    let b = &b[..len];
+
+    #[cfg(feature = "simd")]
+    {
+        const LANES: usize = 4;
+        if len < LANES {
+            return a.iter().zip(b).map(|(&x, &y)| scalar_op(x, y)).collect();
+        }
+
+        let a_chunks = a.chunks_exact(LANES);
+        let b_chunks = b.chunks_exact(LANES);
+        let a_rem = a_chunks.remainder();
+        let b_rem = b_chunks.remainder();
+
+        let mut out = Vec::with_capacity(len);
+        for (ac, bc) in a_chunks.zip(b_chunks) {
+            let va = f32x4::from([ac[0], ac[1], ac[2], ac[3]]);
+            let vb = f32x4::from([bc[0], bc[1], bc[2], bc[3]]);
+            out.extend_from_slice(&simd_op(va, vb).to_array());
+        }
+        out.extend(a_rem.iter().zip(b_rem).map(|(&x, &y)| scalar_op(x, y)));
+        out
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        a.iter().zip(b).map(|(&x, &y)| scalar_op(x, y)).collect()
+    }
+}
+
 /// Parallel accumulation for u32 values (for graph operations)
 #[inline(always)]
 pub fn sum_u32(values: &[u32]) -> u32 {
@@ -211,4 +355,55 @@ mod tests {
         let values = [5.0, 1.0, 3.0, 8.0, 2.0];
         assert_eq!(argmin_f32(&values), Some(1));
     }
+
+    #[test]
+    fn test_dot_f32() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [2.0, 2.0, 2.0, 2.0, 2.0];
+        assert!((dot_f32(&a, &b) - 30.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_dot_f32_uses_min_length() {
+        let a = [1.0, 2.0, 3.0];
+        let b = [4.0, 5.0];
+        assert!((dot_f32(&a, &b) - 14.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_cosine_f32() {
+        let a = [1.0, 0.0, 0.0, 0.0];
+        let b = [1.0, 0.0, 0.0, 0.0];
+        assert!((cosine_f32(&a, &b).unwrap() - 1.0).abs() < 0.001);
+
+        let c = [0.0, 1.0, 0.0, 0.0];
+        assert!(cosine_f32(&a, &c).unwrap().abs() < 0.001);
+    }
+
+    #[test]
+    fn test_cosine_f32_zero_norm() {
+        let a = [0.0, 0.0, 0.0, 0.0];
+        let b = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(cosine_f32(&a, &b), None);
+    }
+
+    #[test]
+    fn test_add_f32() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [10.0, 20.0, 30.0, 40.0, 50.0];
+        assert_eq!(add_f32(&a, &b), vec![11.0, 22.0, 33.0, 44.0, 55.0]);
+    }
+
+    #[test]
+    fn test_mul_f32() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [2.0, 3.0, 4.0, 5.0, 6.0];
+        assert_eq!(mul_f32(&a, &b), vec![2.0, 6.0, 12.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn test_scale_f32() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(scale_f32(&a, 2.0), vec![2.0, 4.0, 6.0, 8.0, 10.0]);
+    }
 }
@@ -0,0 +1,29 @@
+//! Fuzz target: audit appends must never panic or break the SHA-256 chain.
+//!
+//! Drives `append_audit_impl` with an arbitrary chain blob and action blob and
+//! asserts that, when an append succeeds, the returned entry links to the prior
+//! `latest_hash` (R6 chain integrity).
+
+#![no_main]
+use honggfuzz::fuzz;
+use governance_wasm::append_audit_impl;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let Ok(text) = std::str::from_utf8(data) else { return };
+            let mut parts = text.splitn(2, '\0');
+            let chain = parts.next().unwrap_or("");
+            let action = parts.next().unwrap_or("");
+            if let Ok(out) = append_audit_impl(chain, action) {
+                let prior: serde_json::Value = serde_json::from_str(chain).unwrap();
+                let out: serde_json::Value = serde_json::from_str(&out).unwrap();
+                assert_eq!(
+                    out["action"]["reference"]["previous_hash"],
+                    prior["latest_hash"],
+                    "appended entry does not link to the prior latest_hash"
+                );
+            }
+        });
+    }
+}
@@ -0,0 +1,24 @@
+//! Fuzz target: policy evaluation must never panic on arbitrary input.
+//!
+//! Drives `evaluate_policy_impl` with arbitrary UTF-8 split into a policy blob,
+//! a tool name, an entity-trust blob, and a rate-limiter state blob. A crash
+//! here is a bug; malformed JSON must surface as an `Err`, not a panic.
+
+#![no_main]
+use honggfuzz::fuzz;
+use governance_wasm::evaluate_policy_impl;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let Ok(text) = std::str::from_utf8(data) else { return };
+            // Partition the input into four fields on NUL bytes.
+            let mut parts = text.splitn(4, '\0');
+            let policy = parts.next().unwrap_or("");
+            let tool = parts.next().unwrap_or("Read");
+            let entity = parts.next().unwrap_or("");
+            let state = parts.next().unwrap_or("");
+            let _ = evaluate_policy_impl(policy, tool, None, entity, state);
+        });
+    }
+}
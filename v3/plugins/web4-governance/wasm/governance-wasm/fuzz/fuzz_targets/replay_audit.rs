@@ -0,0 +1,21 @@
+//! Fuzz target: trace replay must never panic and must keep the chain linked.
+//!
+//! Feeds arbitrary bytes to `replay_audit_impl` as a compact-binary trace. A
+//! successful replay must report `verified: true` — any replay that rebuilds a
+//! chain whose entries do not link is a chain-integrity regression, so the
+//! corpus doubles as a replay fixture set.
+
+#![no_main]
+use honggfuzz::fuzz;
+use governance_wasm::replay_audit_impl;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            if let Ok(out) = replay_audit_impl(data) {
+                let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+                assert_eq!(parsed["verified"], true, "replayed chain failed integrity check");
+            }
+        });
+    }
+}
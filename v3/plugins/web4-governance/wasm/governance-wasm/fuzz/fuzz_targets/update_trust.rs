@@ -0,0 +1,21 @@
+//! Fuzz target: trust updates must never panic on arbitrary input.
+//!
+//! Drives `update_trust_impl` with an arbitrary entity blob and tool name, for
+//! both success and failure outcomes.
+
+#![no_main]
+use honggfuzz::fuzz;
+use governance_wasm::update_trust_impl;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let Ok(text) = std::str::from_utf8(data) else { return };
+            let mut parts = text.splitn(2, '\0');
+            let entity = parts.next().unwrap_or("");
+            let tool = parts.next().unwrap_or("Read");
+            let _ = update_trust_impl(entity, tool, true, 0.1);
+            let _ = update_trust_impl(entity, tool, false, 0.1);
+        });
+    }
+}
@@ -76,6 +76,17 @@ pub struct R6Result {
     pub error: Option<String>,
 }
 
+/// Default content-hash width in bytes.
+///
+/// The truncated 8-byte hashes the chain shipped with are too weak to anchor a
+/// Merkle tree, so new chains default to the full 32-byte SHA-256 digest.
+const DEFAULT_HASH_WIDTH: usize = 32;
+
+#[inline]
+fn default_hash_width() -> usize {
+    DEFAULT_HASH_WIDTH
+}
+
 /// Audit chain state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditChain {
@@ -85,6 +96,12 @@ pub struct AuditChain {
     pub latest_hash: Option<String>,
     pub sequence_number: u64,
     pub created_at: String,
+    /// Merkle root over all action content hashes, recomputed on every append
+    #[serde(default)]
+    pub merkle_root: Option<String>,
+    /// Width (in bytes) of the stored content hashes
+    #[serde(default = "default_hash_width")]
+    pub hash_width: usize,
 }
 
 impl AuditChain {
@@ -96,6 +113,8 @@ impl AuditChain {
             latest_hash: None,
             sequence_number: 0,
             created_at: js_sys::Date::new_0().to_iso_string().as_string().unwrap_or_default(),
+            merkle_root: None,
+            hash_width: DEFAULT_HASH_WIDTH,
         }
     }
 
@@ -104,17 +123,268 @@ impl AuditChain {
         self.entries.push(hash.clone());
         self.latest_hash = Some(hash.clone());
         self.sequence_number += 1;
+        self.merkle_root = merkle_root(&self.entries);
         hash
     }
 }
 
-/// Create action content hash
+/// Create action content hash at the full digest width
 fn compute_action_hash(action: &R6ActionInput) -> String {
+    compute_action_hash_width(action, DEFAULT_HASH_WIDTH)
+}
+
+/// Create action content hash truncated to `width` bytes
+fn compute_action_hash_width(action: &R6ActionInput, width: usize) -> String {
     let content = serde_json::to_string(action).unwrap_or_default();
+    hash_hex(content.as_bytes(), width)
+}
+
+/// SHA-256 of `bytes`, hex-encoded and truncated to `width` bytes
+fn hash_hex(bytes: &[u8], width: usize) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
+    hasher.update(bytes);
     let result = hasher.finalize();
-    result.iter().take(8).map(|b| format!("{:02x}", b)).collect()
+    result.iter().take(width).map(|b| format!("{:02x}", b)).collect()
+}
+
+// ============================================================================
+// Merkle verification
+// ============================================================================
+
+/// Hash a pair of child nodes (full 32-byte digest over the concatenated hex)
+fn hash_pair(left: &str, right: &str) -> String {
+    let mut data = String::with_capacity(left.len() + right.len());
+    data.push_str(left);
+    data.push_str(right);
+    hash_hex(data.as_bytes(), DEFAULT_HASH_WIDTH)
+}
+
+/// Recompute the Merkle root over a list of leaf content hashes.
+///
+/// Builds the tree bottom-up, duplicating the last node when a level has an odd
+/// count. Returns `None` for an empty chain.
+fn merkle_root(leaves: &[String]) -> Option<String> {
+    if leaves.is_empty() {
+        return None;
+    }
+
+    let mut level: Vec<String> = leaves.to_vec();
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        let mut i = 0;
+        while i < level.len() {
+            let left = &level[i];
+            let right = if i + 1 < level.len() { &level[i + 1] } else { left };
+            next.push(hash_pair(left, right));
+            i += 2;
+        }
+        level = next;
+    }
+
+    Some(level.remove(0))
+}
+
+/// One step of a Merkle inclusion proof
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleProofStep {
+    /// Sibling hash at this level
+    pub sibling: String,
+    /// Whether the sibling is the left child (our node is the right child)
+    pub is_left: bool,
+}
+
+/// Merkle inclusion proof for a single audit record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub action_id: String,
+    pub leaf_index: usize,
+    pub leaf_hash: String,
+    pub path: Vec<MerkleProofStep>,
+    pub merkle_root: String,
+}
+
+/// Result of verifying a full chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainVerification {
+    pub valid: bool,
+    pub merkle_root: Option<String>,
+    /// First action whose hash, linkage, or sequence number failed to verify
+    pub offending_action: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Reconstruct the hash input for an already-recorded action
+fn input_from_action(action: &R6Action) -> R6ActionInput {
+    R6ActionInput {
+        policy_id: action.rules.policy_id.clone(),
+        policy_hash: action.rules.policy_hash.clone(),
+        matched_rule: action.rules.matched_rule.clone(),
+        decision: action.rules.decision,
+        session_id: action.role.session_id.clone(),
+        agent_id: action.role.agent_id.clone(),
+        trust_score: action.role.trust_score,
+        tool_name: action.request.tool_name.clone(),
+        parameters_hash: action.request.parameters_hash.clone(),
+        target: action.resource.target.clone(),
+        success: action.result.success,
+        enforced: action.result.enforced,
+        blocked: action.result.blocked,
+        error: action.result.error.clone(),
+    }
+}
+
+/// Build the inclusion proof for the leaf at `index`
+fn merkle_proof_path(leaves: &[String], mut index: usize) -> Vec<MerkleProofStep> {
+    let mut path = Vec::new();
+    let mut level: Vec<String> = leaves.to_vec();
+
+    while level.len() > 1 {
+        let is_left = index % 2 == 0;
+        let sibling = if is_left {
+            let sib = index + 1;
+            if sib < level.len() { level[sib].clone() } else { level[index].clone() }
+        } else {
+            level[index - 1].clone()
+        };
+        path.push(MerkleProofStep { sibling, is_left: !is_left });
+
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        let mut i = 0;
+        while i < level.len() {
+            let left = &level[i];
+            let right = if i + 1 < level.len() { &level[i + 1] } else { left };
+            next.push(hash_pair(left, right));
+            i += 2;
+        }
+        level = next;
+        index /= 2;
+    }
+
+    path
+}
+
+/// Verify a chain end-to-end: content hashes, linkage, sequence, and Merkle root.
+///
+/// Returns the first offending `action_id` on any mismatch.
+pub fn verify_chain_impl(chain_json: &str, actions_json: &str) -> Result<String, JsValue> {
+    let chain: AuditChain = serde_json::from_str(chain_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid chain JSON: {}", e)))?;
+
+    let actions: Vec<R6Action> = serde_json::from_str(actions_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid actions JSON: {}", e)))?;
+
+    let offending = |action: &R6Action, reason: &str| ChainVerification {
+        valid: false,
+        merkle_root: chain.merkle_root.clone(),
+        offending_action: Some(action.action_id.clone()),
+        reason: Some(reason.to_string()),
+    };
+
+    let mut previous_hash: Option<String> = None;
+    let mut expected_seq: u64 = 1;
+
+    for action in &actions {
+        // Recompute the content hash from the recorded fields
+        let recomputed = compute_action_hash_width(&input_from_action(action), chain.hash_width);
+        if recomputed != action.content_hash {
+            return finish(offending(action, "content_hash mismatch"));
+        }
+
+        // Check linkage to the prior entry
+        if action.reference.previous_hash != previous_hash {
+            return finish(offending(action, "previous_hash does not link to prior entry"));
+        }
+
+        // Check sequence contiguity
+        if action.reference.sequence_number != expected_seq {
+            return finish(offending(action, "sequence_number is not contiguous"));
+        }
+
+        previous_hash = Some(action.content_hash.clone());
+        expected_seq += 1;
+    }
+
+    // Recompute the Merkle root and compare to the stored root
+    let leaves: Vec<String> = actions.iter().map(|a| a.content_hash.clone()).collect();
+    let recomputed_root = merkle_root(&leaves);
+    if recomputed_root != chain.merkle_root {
+        let result = ChainVerification {
+            valid: false,
+            merkle_root: recomputed_root,
+            offending_action: actions.last().map(|a| a.action_id.clone()),
+            reason: Some("merkle_root mismatch".to_string()),
+        };
+        return finish(result);
+    }
+
+    finish(ChainVerification {
+        valid: true,
+        merkle_root: recomputed_root,
+        offending_action: None,
+        reason: None,
+    })
+}
+
+/// Generate a Merkle inclusion proof for a single action in the chain
+pub fn generate_proof_impl(chain_json: &str, action_id: &str) -> Result<String, JsValue> {
+    let chain: AuditChain = serde_json::from_str(chain_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid chain JSON: {}", e)))?;
+
+    // action_id is "r6:{session}:{sequence}"; leaf index is sequence - 1
+    let seq: usize = action_id
+        .rsplit(':')
+        .next()
+        .and_then(|s| s.parse::<usize>().ok())
+        .ok_or_else(|| JsValue::from_str("Malformed action_id"))?;
+    let index = seq.checked_sub(1)
+        .ok_or_else(|| JsValue::from_str("action_id sequence must be >= 1"))?;
+
+    if index >= chain.entries.len() {
+        return Err(JsValue::from_str("action_id not found in chain"));
+    }
+
+    let root = merkle_root(&chain.entries)
+        .ok_or_else(|| JsValue::from_str("Empty chain has no Merkle root"))?;
+
+    let proof = MerkleProof {
+        action_id: action_id.to_string(),
+        leaf_index: index,
+        leaf_hash: chain.entries[index].clone(),
+        path: merkle_proof_path(&chain.entries, index),
+        merkle_root: root,
+    };
+
+    serde_json::to_string(&proof)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Verify a Merkle inclusion proof against an expected root
+pub fn verify_proof_impl(proof_json: &str) -> Result<String, JsValue> {
+    let proof: MerkleProof = serde_json::from_str(proof_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid proof JSON: {}", e)))?;
+
+    let mut hash = proof.leaf_hash.clone();
+    for step in &proof.path {
+        hash = if step.is_left {
+            hash_pair(&step.sibling, &hash)
+        } else {
+            hash_pair(&hash, &step.sibling)
+        };
+    }
+
+    let valid = hash == proof.merkle_root;
+    serde_json::to_string(&serde_json::json!({
+        "valid": valid,
+        "computed_root": hash,
+        "expected_root": proof.merkle_root,
+    }))
+    .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Serialize a chain-verification result
+fn finish(result: ChainVerification) -> Result<String, JsValue> {
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
 }
 
 /// Input for creating an R6 action
@@ -136,19 +406,13 @@ pub struct R6ActionInput {
     pub error: Option<String>,
 }
 
-/// Append to audit chain
-pub fn append_audit_impl(
-    chain_json: &str,
-    action_json: &str,
-) -> Result<String, JsValue> {
-    let mut chain: AuditChain = serde_json::from_str(chain_json)
-        .map_err(|e| JsValue::from_str(&format!("Invalid chain JSON: {}", e)))?;
-
-    let input: R6ActionInput = serde_json::from_str(action_json)
-        .map_err(|e| JsValue::from_str(&format!("Invalid action JSON: {}", e)))?;
-
+/// Build an R6 action from an input and append it to the chain.
+///
+/// Shared by the JSON [`append_audit_impl`] boundary and the deterministic
+/// [`crate::replay::replay_audit_impl`] re-executor.
+pub fn append_action(chain: &mut AuditChain, input: R6ActionInput) -> R6Action {
     let category = ToolCategory::from_tool_name(&input.tool_name);
-    let content_hash = compute_action_hash(&input);
+    let content_hash = compute_action_hash_width(&input, chain.hash_width);
     let now = js_sys::Date::new_0().to_iso_string().as_string().unwrap_or_default();
 
     let action = R6Action {
@@ -184,15 +448,31 @@ pub fn append_audit_impl(
             error: input.error,
         },
         timestamp: now,
-        content_hash: content_hash.clone(),
+        content_hash,
     };
 
     chain.append(&action);
+    action
+}
+
+/// Append to audit chain
+pub fn append_audit_impl(
+    chain_json: &str,
+    action_json: &str,
+) -> Result<String, JsValue> {
+    let mut chain: AuditChain = serde_json::from_str(chain_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid chain JSON: {}", e)))?;
+
+    let input: R6ActionInput = serde_json::from_str(action_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid action JSON: {}", e)))?;
+
+    let action = append_action(&mut chain, input);
+    let new_hash = action.content_hash.clone();
 
     let response = serde_json::json!({
         "chain": chain,
         "action": action,
-        "new_hash": content_hash,
+        "new_hash": new_hash,
     });
 
     serde_json::to_string(&response)
@@ -251,4 +531,70 @@ mod tests {
         assert_eq!(action.action_id, "r6:test:1");
         assert_eq!(action.rules.decision, PolicyDecision::Allow);
     }
+
+    #[test]
+    fn test_merkle_root_single_and_odd() {
+        // Single leaf: root is a self-pair? No - a lone leaf is its own root.
+        let one = vec!["aa".to_string()];
+        assert_eq!(merkle_root(&one), Some("aa".to_string()));
+
+        // Odd count duplicates the last node, so three leaves produce a root.
+        let three = vec!["aa".to_string(), "bb".to_string(), "cc".to_string()];
+        assert!(merkle_root(&three).is_some());
+        // Empty chain has no root.
+        assert_eq!(merkle_root(&[]), None);
+    }
+
+    #[test]
+    fn test_merkle_proof_roundtrip() {
+        let leaves: Vec<String> =
+            (0..5).map(|i| hash_hex(format!("leaf{}", i).as_bytes(), 32)).collect();
+        let root = merkle_root(&leaves).unwrap();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let path = merkle_proof_path(&leaves, index);
+            let mut hash = leaf.clone();
+            for step in &path {
+                hash = if step.is_left {
+                    hash_pair(&step.sibling, &hash)
+                } else {
+                    hash_pair(&hash, &step.sibling)
+                };
+            }
+            assert_eq!(hash, root, "proof for leaf {} failed", index);
+        }
+    }
+
+    #[test]
+    fn test_append_updates_merkle_root() {
+        let mut chain = AuditChain::new("session:a".to_string(), "policy:default".to_string());
+        assert_eq!(chain.merkle_root, None);
+
+        let mut action = R6Action {
+            action_id: "r6:session:a:1".to_string(),
+            rules: R6Rules {
+                policy_id: "p".to_string(),
+                policy_hash: "h".to_string(),
+                matched_rule: None,
+                decision: PolicyDecision::Allow,
+            },
+            role: R6Role { session_id: "session:a".to_string(), agent_id: None, trust_score: 0.5 },
+            request: R6Request {
+                tool_name: "Read".to_string(),
+                category: ToolCategory::FileRead,
+                parameters_hash: "x".to_string(),
+            },
+            reference: R6Reference { previous_hash: None, sequence_number: 1 },
+            resource: R6Resource { target: None, target_type: "file_read".to_string() },
+            result: R6Result { success: true, enforced: true, blocked: false, error: None },
+            timestamp: "2026-01-31T00:00:00Z".to_string(),
+            content_hash: hash_hex(b"one", 32),
+        };
+        chain.append(&action);
+        assert_eq!(chain.merkle_root, Some(chain.entries[0].clone()));
+
+        action.content_hash = hash_hex(b"two", 32);
+        chain.append(&action);
+        assert_eq!(chain.merkle_root, merkle_root(&chain.entries));
+    }
 }
@@ -0,0 +1,760 @@
+//! Compact binary codec for the governance hot path
+//!
+//! Every exported function takes and returns JSON strings, which forces a full
+//! `serde_json` parse/serialize on the documented sub-0.1ms path. Borrowing the
+//! idea of a derive-generated SCALE codec, this module gives the evaluation
+//! types a compact, deterministic byte layout so hosts can hand JS
+//! `Uint8Array`s instead of UTF-8 JSON. The policy blob is still decoded on
+//! every evaluation call; no decoded policy is cached.
+//!
+//! We implement the encoding in-crate rather than pulling in
+//! `parity-scale-codec`, matching the policy module's WASM-size discipline
+//! (it embeds a tiny regex engine rather than the full regex crate). Integers and lengths are
+//! little-endian, `Vec`/`String` carry a `u32` length prefix, and `f64` is
+//! encoded by its IEEE-754 bit pattern so hashes over the bytes are stable.
+
+use wasm_bindgen::prelude::*;
+use crate::{PolicyDecision, PolicyEvaluation, ToolCategory, T3Tensor, V3Tensor};
+use crate::policy::{
+    AggregateCondition, AggregateMetric, CompareOp, Condition, EntityTrust, MatchOp, PolicyConfig,
+    PolicyMatch, PolicyRule, RateLimitSpec, RegexReplace, RuleMatch, TargetMatcher,
+};
+use gastown_shared::FxHashMap;
+
+/// A decode failure against a binary buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodecError(pub &'static str);
+
+/// SCALE-style binary encoder.
+pub trait Encode {
+    fn encode_to(&self, out: &mut Vec<u8>);
+
+    #[inline]
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_to(&mut out);
+        out
+    }
+}
+
+/// SCALE-style binary decoder.
+pub trait Decode: Sized {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError>;
+}
+
+/// Split `n` bytes off the front of `input`, advancing the cursor.
+#[inline]
+fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], CodecError> {
+    if input.len() < n {
+        return Err(CodecError("unexpected end of input"));
+    }
+    let (head, tail) = input.split_at(n);
+    *input = tail;
+    Ok(head)
+}
+
+// --- Primitives -------------------------------------------------------------
+
+impl Encode for u8 {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+impl Decode for u8 {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(take(input, 1)?[0])
+    }
+}
+
+impl Encode for bool {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+impl Decode for bool {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(u8::decode(input)? != 0)
+    }
+}
+
+impl Encode for u32 {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+impl Decode for u32 {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        let bytes: [u8; 4] = take(input, 4)?.try_into().unwrap();
+        Ok(u32::from_le_bytes(bytes))
+    }
+}
+
+impl Encode for u64 {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+impl Decode for u64 {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        let bytes: [u8; 8] = take(input, 8)?.try_into().unwrap();
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+impl Encode for f64 {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        // Encode the raw bit pattern for a deterministic layout.
+        out.extend_from_slice(&self.to_bits().to_le_bytes());
+    }
+}
+impl Decode for f64 {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        let bytes: [u8; 8] = take(input, 8)?.try_into().unwrap();
+        Ok(f64::from_bits(u64::from_le_bytes(bytes)))
+    }
+}
+
+impl Encode for str {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode_to(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+impl Encode for String {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.as_str().encode_to(out);
+    }
+}
+impl Decode for String {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        let len = u32::decode(input)? as usize;
+        let bytes = take(input, len)?;
+        core::str::from_utf8(bytes)
+            .map(|s| s.to_string())
+            .map_err(|_| CodecError("invalid utf-8"))
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode_to(out);
+        for item in self {
+            item.encode_to(out);
+        }
+    }
+}
+impl<T: Decode> Decode for Vec<T> {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        let len = u32::decode(input)? as usize;
+        // Cap the pre-allocation by the bytes left, so an untrusted length
+        // prefix cannot force a huge allocation before decoding fails.
+        let mut v = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            v.push(T::decode(input)?);
+        }
+        Ok(v)
+    }
+}
+
+/// String-keyed maps are encoded as a length-prefixed list of key/value pairs
+/// sorted by key, so the byte layout stays deterministic regardless of the
+/// hash map's iteration order.
+impl<V: Encode> Encode for FxHashMap<String, V> {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        let mut entries: Vec<(&String, &V)> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        (entries.len() as u32).encode_to(out);
+        for (key, value) in entries {
+            key.encode_to(out);
+            value.encode_to(out);
+        }
+    }
+}
+impl<V: Decode> Decode for FxHashMap<String, V> {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        let len = u32::decode(input)? as usize;
+        let mut map = FxHashMap::default();
+        for _ in 0..len {
+            let key = String::decode(input)?;
+            let value = V::decode(input)?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(v) => {
+                out.push(1);
+                v.encode_to(out);
+            }
+        }
+    }
+}
+impl<T: Decode> Decode for Option<T> {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        match u8::decode(input)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(input)?)),
+            _ => Err(CodecError("invalid Option tag")),
+        }
+    }
+}
+
+// --- Enums (single-byte discriminant) --------------------------------------
+
+impl Encode for PolicyDecision {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        let tag = match self {
+            PolicyDecision::Allow => 0u8,
+            PolicyDecision::Deny => 1,
+            PolicyDecision::AskUser => 2,
+            PolicyDecision::LogOnly => 3,
+        };
+        out.push(tag);
+    }
+}
+impl Decode for PolicyDecision {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(match u8::decode(input)? {
+            0 => PolicyDecision::Allow,
+            1 => PolicyDecision::Deny,
+            2 => PolicyDecision::AskUser,
+            3 => PolicyDecision::LogOnly,
+            _ => return Err(CodecError("invalid PolicyDecision tag")),
+        })
+    }
+}
+
+impl Encode for ToolCategory {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        let tag = match self {
+            ToolCategory::FileRead => 0u8,
+            ToolCategory::FileWrite => 1,
+            ToolCategory::Execute => 2,
+            ToolCategory::Network => 3,
+            ToolCategory::Agent => 4,
+            ToolCategory::Memory => 5,
+            ToolCategory::System => 6,
+        };
+        out.push(tag);
+    }
+}
+impl Decode for ToolCategory {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(match u8::decode(input)? {
+            0 => ToolCategory::FileRead,
+            1 => ToolCategory::FileWrite,
+            2 => ToolCategory::Execute,
+            3 => ToolCategory::Network,
+            4 => ToolCategory::Agent,
+            5 => ToolCategory::Memory,
+            6 => ToolCategory::System,
+            _ => return Err(CodecError("invalid ToolCategory tag")),
+        })
+    }
+}
+
+// --- Structs ----------------------------------------------------------------
+
+impl Encode for T3Tensor {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.talent.encode_to(out);
+        self.training.encode_to(out);
+        self.temperament.encode_to(out);
+    }
+}
+impl Decode for T3Tensor {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(T3Tensor {
+            talent: f64::decode(input)?,
+            training: f64::decode(input)?,
+            temperament: f64::decode(input)?,
+        })
+    }
+}
+
+impl Encode for V3Tensor {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.valuation.encode_to(out);
+        self.veracity.encode_to(out);
+        self.validity.encode_to(out);
+    }
+}
+impl Decode for V3Tensor {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(V3Tensor {
+            valuation: f64::decode(input)?,
+            veracity: f64::decode(input)?,
+            validity: f64::decode(input)?,
+        })
+    }
+}
+
+impl Encode for EntityTrust {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.entity_id.encode_to(out);
+        self.t3.encode_to(out);
+        self.v3.encode_to(out);
+        self.interaction_count.encode_to(out);
+        self.roles.encode_to(out);
+    }
+}
+impl Decode for EntityTrust {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(EntityTrust {
+            entity_id: String::decode(input)?,
+            t3: T3Tensor::decode(input)?,
+            v3: Option::decode(input)?,
+            interaction_count: u64::decode(input)?,
+            roles: Vec::decode(input)?,
+        })
+    }
+}
+
+impl Encode for RateLimitSpec {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.max_count.encode_to(out);
+        self.window_ms.encode_to(out);
+    }
+}
+impl Decode for RateLimitSpec {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(RateLimitSpec {
+            max_count: u32::decode(input)?,
+            window_ms: u64::decode(input)?,
+        })
+    }
+}
+
+impl Encode for MatchOp {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        let tag = match self {
+            MatchOp::Equal => 0u8,
+            MatchOp::StartsWith => 1,
+            MatchOp::EndsWith => 2,
+            MatchOp::Contains => 3,
+            MatchOp::Glob => 4,
+            MatchOp::Regex => 5,
+        };
+        out.push(tag);
+    }
+}
+impl Decode for MatchOp {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(match u8::decode(input)? {
+            0 => MatchOp::Equal,
+            1 => MatchOp::StartsWith,
+            2 => MatchOp::EndsWith,
+            3 => MatchOp::Contains,
+            4 => MatchOp::Glob,
+            5 => MatchOp::Regex,
+            _ => return Err(CodecError("invalid MatchOp tag")),
+        })
+    }
+}
+
+impl Encode for TargetMatcher {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.op.encode_to(out);
+        self.value.encode_to(out);
+    }
+}
+impl Decode for TargetMatcher {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(TargetMatcher {
+            op: MatchOp::decode(input)?,
+            value: String::decode(input)?,
+        })
+    }
+}
+
+impl Encode for PolicyMatch {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.tools.encode_to(out);
+        self.categories.encode_to(out);
+        self.target_patterns.encode_to(out);
+        self.rate_limit.encode_to(out);
+        self.min_trust.encode_to(out);
+        self.roles.encode_to(out);
+        self.normalize.encode_to(out);
+        self.aggregate.encode_to(out);
+    }
+}
+impl Decode for PolicyMatch {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(PolicyMatch {
+            tools: Option::decode(input)?,
+            categories: Option::decode(input)?,
+            target_patterns: Option::decode(input)?,
+            rate_limit: Option::decode(input)?,
+            min_trust: Option::decode(input)?,
+            roles: Option::decode(input)?,
+            normalize: Option::decode(input)?,
+            aggregate: Option::decode(input)?,
+        })
+    }
+}
+
+impl Encode for RegexReplace {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.pattern.encode_to(out);
+        self.replacement.encode_to(out);
+    }
+}
+impl Decode for RegexReplace {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(RegexReplace {
+            pattern: String::decode(input)?,
+            replacement: String::decode(input)?,
+        })
+    }
+}
+
+impl Encode for AggregateMetric {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        let tag = match self {
+            AggregateMetric::RecentCalls => 0u8,
+            AggregateMetric::RecentDenies => 1,
+        };
+        out.push(tag);
+    }
+}
+impl Decode for AggregateMetric {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(match u8::decode(input)? {
+            0 => AggregateMetric::RecentCalls,
+            1 => AggregateMetric::RecentDenies,
+            _ => return Err(CodecError("invalid AggregateMetric tag")),
+        })
+    }
+}
+
+impl Encode for CompareOp {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        let tag = match self {
+            CompareOp::Gt => 0u8,
+            CompareOp::Gte => 1,
+            CompareOp::Lt => 2,
+            CompareOp::Lte => 3,
+            CompareOp::Eq => 4,
+        };
+        out.push(tag);
+    }
+}
+impl Decode for CompareOp {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(match u8::decode(input)? {
+            0 => CompareOp::Gt,
+            1 => CompareOp::Gte,
+            2 => CompareOp::Lt,
+            3 => CompareOp::Lte,
+            4 => CompareOp::Eq,
+            _ => return Err(CodecError("invalid CompareOp tag")),
+        })
+    }
+}
+
+impl Encode for AggregateCondition {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.metric.encode_to(out);
+        self.op.encode_to(out);
+        self.window_ms.encode_to(out);
+        self.threshold.encode_to(out);
+    }
+}
+impl Decode for AggregateCondition {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(AggregateCondition {
+            metric: AggregateMetric::decode(input)?,
+            op: CompareOp::decode(input)?,
+            window_ms: u64::decode(input)?,
+            threshold: u32::decode(input)?,
+        })
+    }
+}
+
+impl Encode for RuleMatch {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        match self {
+            RuleMatch::Flat(spec) => {
+                out.push(0u8);
+                spec.encode_to(out);
+            }
+            RuleMatch::Condition(cond) => {
+                out.push(1u8);
+                cond.encode_to(out);
+            }
+        }
+    }
+}
+impl Decode for RuleMatch {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(match u8::decode(input)? {
+            0 => RuleMatch::Flat(PolicyMatch::decode(input)?),
+            1 => RuleMatch::Condition(Condition::decode(input)?),
+            _ => return Err(CodecError("invalid RuleMatch tag")),
+        })
+    }
+}
+
+impl Encode for Condition {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        match self {
+            Condition::All(children) => {
+                out.push(0u8);
+                children.encode_to(out);
+            }
+            Condition::Any(children) => {
+                out.push(1u8);
+                children.encode_to(out);
+            }
+            Condition::Not(child) => {
+                out.push(2u8);
+                child.encode_to(out);
+            }
+            Condition::Leaf(spec) => {
+                out.push(3u8);
+                spec.encode_to(out);
+            }
+        }
+    }
+}
+impl Decode for Condition {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(match u8::decode(input)? {
+            0 => Condition::All(Vec::decode(input)?),
+            1 => Condition::Any(Vec::decode(input)?),
+            2 => Condition::Not(Box::new(Condition::decode(input)?)),
+            3 => Condition::Leaf(PolicyMatch::decode(input)?),
+            _ => return Err(CodecError("invalid Condition tag")),
+        })
+    }
+}
+
+impl Encode for PolicyRule {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.id.encode_to(out);
+        self.name.encode_to(out);
+        self.priority.encode_to(out);
+        self.match_spec.encode_to(out);
+        self.decision.encode_to(out);
+        self.reason.encode_to(out);
+    }
+}
+impl Decode for PolicyRule {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(PolicyRule {
+            id: String::decode(input)?,
+            name: String::decode(input)?,
+            priority: u32::decode(input)?,
+            match_spec: RuleMatch::decode(input)?,
+            decision: PolicyDecision::decode(input)?,
+            reason: Option::decode(input)?,
+        })
+    }
+}
+
+impl Encode for PolicyConfig {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.name.encode_to(out);
+        self.version.encode_to(out);
+        self.enforce.encode_to(out);
+        self.default_policy.encode_to(out);
+        self.rules.encode_to(out);
+        self.role_grants.encode_to(out);
+    }
+}
+impl Decode for PolicyConfig {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(PolicyConfig {
+            name: String::decode(input)?,
+            version: String::decode(input)?,
+            enforce: bool::decode(input)?,
+            default_policy: PolicyDecision::decode(input)?,
+            rules: Vec::decode(input)?,
+            role_grants: FxHashMap::decode(input)?,
+        })
+    }
+}
+
+impl Encode for PolicyEvaluation {
+    #[inline]
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.decision.encode_to(out);
+        self.matched_rule.encode_to(out);
+        self.enforced.encode_to(out);
+        self.reason.encode_to(out);
+        self.trust_score.encode_to(out);
+        self.constraints.encode_to(out);
+    }
+}
+impl Decode for PolicyEvaluation {
+    #[inline]
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(PolicyEvaluation {
+            decision: PolicyDecision::decode(input)?,
+            matched_rule: Option::decode(input)?,
+            enforced: bool::decode(input)?,
+            reason: String::decode(input)?,
+            trust_score: f64::decode(input)?,
+            constraints: Vec::decode(input)?,
+        })
+    }
+}
+
+/// Evaluate a tool call against a compact-binary policy and entity-trust blob.
+///
+/// Trades JSON for the SCALE-style layout. Both blobs are decoded on every
+/// call, and evaluation goes through the stateless [`crate::policy::evaluate`]:
+/// no rate-limiter state is taken or returned, so aggregate rules fail closed.
+pub fn evaluate_policy_bin_impl(
+    policy: &[u8],
+    tool_name: &str,
+    target: Option<&str>,
+    entity_trust: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let mut policy_cursor = policy;
+    let policy = PolicyConfig::decode(&mut policy_cursor)
+        .map_err(|e| JsValue::from_str(&format!("Invalid policy blob: {}", e.0)))?;
+
+    let mut entity_cursor = entity_trust;
+    let entity = EntityTrust::decode(&mut entity_cursor)
+        .map_err(|e| JsValue::from_str(&format!("Invalid entity trust blob: {}", e.0)))?;
+
+    let result = crate::policy::evaluate(&policy, tool_name, target, &entity);
+    Ok(result.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitive_roundtrip() {
+        for v in [0u64, 1, u64::MAX, 42] {
+            let bytes = v.encode();
+            let mut cursor = bytes.as_slice();
+            assert_eq!(u64::decode(&mut cursor).unwrap(), v);
+            assert!(cursor.is_empty());
+        }
+        let s = "hello world".to_string();
+        let bytes = s.encode();
+        let mut cursor = bytes.as_slice();
+        assert_eq!(String::decode(&mut cursor).unwrap(), s);
+    }
+
+    #[test]
+    fn test_f64_deterministic() {
+        let t = T3Tensor { talent: 0.3, training: 0.4, temperament: 0.3 };
+        let a = t.encode();
+        let b = t.encode();
+        assert_eq!(a, b, "encoding must be byte-deterministic");
+        let mut cursor = a.as_slice();
+        let decoded = T3Tensor::decode(&mut cursor).unwrap();
+        assert!((decoded.composite() - t.composite()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_evaluate_bin_matches_core() {
+        let policy = PolicyConfig {
+            name: "test".to_string(),
+            version: "1".to_string(),
+            enforce: true,
+            default_policy: PolicyDecision::Deny,
+            rules: vec![PolicyRule {
+                id: "allow-read".to_string(),
+                name: "Allow reads".to_string(),
+                priority: 0,
+                match_spec: RuleMatch::Flat(PolicyMatch {
+                    tools: Some(vec!["Read".to_string()]),
+                    categories: None,
+                    target_patterns: None,
+                    rate_limit: None,
+                    min_trust: None,
+                    roles: None,
+                    normalize: None,
+                    aggregate: None,
+                }),
+                decision: PolicyDecision::Allow,
+                reason: None,
+            }],
+            role_grants: FxHashMap::default(),
+        };
+        let entity = EntityTrust {
+            entity_id: "e".to_string(),
+            t3: T3Tensor::default(),
+            v3: None,
+            interaction_count: 0,
+            roles: Vec::new(),
+        };
+
+        let out = evaluate_policy_bin_impl(&policy.encode(), "Read", None, &entity.encode()).unwrap();
+        let mut cursor = out.as_slice();
+        let eval = PolicyEvaluation::decode(&mut cursor).unwrap();
+        assert_eq!(eval.decision, PolicyDecision::Allow);
+        assert_eq!(eval.matched_rule.as_deref(), Some("allow-read"));
+    }
+
+    #[test]
+    fn test_truncated_input_errors() {
+        let mut cursor: &[u8] = &[1, 2, 3];
+        assert!(u64::decode(&mut cursor).is_err());
+    }
+}
@@ -57,11 +57,15 @@ mod policy;
 mod trust;
 mod witness;
 mod audit;
+mod codec;
+mod replay;
 
 pub use policy::*;
 pub use trust::*;
 pub use witness::*;
 pub use audit::*;
+pub use codec::*;
+pub use replay::*;
 
 // ============================================================================
 // Core Types - Mirroring web4-trust-core
@@ -176,6 +180,102 @@ impl T3Tensor {
     }
 }
 
+/// V3 Value Tensor - Valuation/Veracity/Validity
+///
+/// Per Web4 spec (t3-v3-tensors.md), V3 measures the value an entity delivers
+/// through three FRACTAL dimensions, the value-side counterpart to [`T3Tensor`].
+///
+/// ## Fractal Structure
+///
+/// - Valuation → (reputation, contribution)
+/// - Veracity → (stewardship, energy)
+/// - Validity → (network, temporal)
+///
+/// ## Composite Formula
+///
+/// `valuation * 0.3 + veracity * 0.4 + validity * 0.3`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct V3Tensor {
+    /// Valuation: assessed worth of the entity's contributions
+    /// Subdimensions: reputation, contribution
+    pub valuation: f64,
+    /// Veracity: truthfulness of claimed outcomes, validated over time
+    /// Subdimensions: stewardship, energy
+    pub veracity: f64,
+    /// Validity: whether delivered value holds up in context
+    /// Subdimensions: network, temporal
+    pub validity: f64,
+}
+
+impl Default for V3Tensor {
+    fn default() -> Self {
+        Self {
+            valuation: 0.5,
+            veracity: 0.5,
+            validity: 0.5,
+        }
+    }
+}
+
+impl V3Tensor {
+    /// Calculate composite value score (weighted average per spec)
+    /// valuation * 0.3 + veracity * 0.4 + validity * 0.3
+    #[inline]
+    pub fn composite(&self) -> f64 {
+        self.valuation * 0.3 + self.veracity * 0.4 + self.validity * 0.3
+    }
+
+    /// Get value level from composite score
+    #[inline]
+    pub fn level(&self) -> TrustLevel {
+        TrustLevel::from_score(self.composite())
+    }
+
+    /// Update from outcome, mirroring [`T3Tensor::update_from_outcome`].
+    ///
+    /// Veracity rises on validated successes; a contested (failed) outcome
+    /// decays validity most sharply, since delivered value did not hold up.
+    #[inline]
+    pub fn update_from_outcome(&mut self, success: bool, is_validated: bool) {
+        let clamp = |v: f64| v.clamp(0.0, 1.0);
+
+        if success {
+            if is_validated {
+                self.valuation = clamp(self.valuation + 0.02);
+                self.veracity = clamp(self.veracity + 0.03);
+                self.validity = clamp(self.validity + 0.01);
+            } else {
+                self.valuation = clamp(self.valuation + 0.005);
+                self.veracity = clamp(self.veracity + 0.008);
+            }
+        } else {
+            self.valuation = clamp(self.valuation - 0.01);
+            self.veracity = clamp(self.veracity - 0.01);
+            self.validity = clamp(self.validity - 0.02);
+        }
+    }
+}
+
+/// Combined trust blending capability trust (T3) with accrued value (V3).
+///
+/// Policies can gate on both dimensions: an entity trusted to perform a role
+/// (T3) that has also delivered value in it (V3). The blend weights
+/// trustworthiness slightly above value.
+pub struct OverallTrust;
+
+impl OverallTrust {
+    /// Weight applied to the T3 (trustworthiness) composite.
+    pub const T3_WEIGHT: f64 = 0.6;
+    /// Weight applied to the V3 (value) composite.
+    pub const V3_WEIGHT: f64 = 0.4;
+
+    /// Blend T3 and V3 composites into a single `trust_score`.
+    #[inline]
+    pub fn combine(t3: &T3Tensor, v3: &V3Tensor) -> f64 {
+        t3.composite() * Self::T3_WEIGHT + v3.composite() * Self::V3_WEIGHT
+    }
+}
+
 /// Policy decision types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -187,7 +287,7 @@ pub enum PolicyDecision {
 }
 
 /// Tool categories for policy matching
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ToolCategory {
     FileRead,
@@ -241,17 +341,143 @@ pub fn init() {
 /// * `tool_name` - Name of the tool being called
 /// * `target` - Optional target (file path, URL, etc.)
 /// * `entity_trust_json` - Entity trust state as JSON
+/// * `state_json` - Rate limiter state as JSON, carrying the recent-call
+///   history that stateful (aggregate) rules read; pass `"null"` or an empty
+///   object to start fresh
 ///
 /// # Returns
-/// * Policy evaluation result as JSON
+/// * JSON `{ "evaluation": ..., "state": ... }` — the updated state records
+///   this call and should be fed back on the next evaluation
 #[wasm_bindgen]
 pub fn evaluate_policy(
     policy_json: &str,
     tool_name: &str,
     target: Option<String>,
     entity_trust_json: &str,
+    state_json: &str,
+) -> Result<String, JsValue> {
+    policy::evaluate_policy_impl(
+        policy_json,
+        tool_name,
+        target.as_deref(),
+        entity_trust_json,
+        state_json,
+    )
+}
+
+/// Evaluate a batch of tool calls against a policy in one call
+///
+/// # Arguments
+/// * `policy_json` - Policy configuration as JSON
+/// * `calls_json` - Array of `{ tool_name, target }` objects
+/// * `entity_trust_json` - Entity trust state as JSON
+///
+/// # Returns
+/// * A combined report as JSON: per-call decisions plus roll-up aggregates
+///   (allowed/denied/warn totals, per-rule fire counts, default-policy calls)
+#[wasm_bindgen]
+pub fn evaluate_policy_batch(
+    policy_json: &str,
+    calls_json: &str,
+    entity_trust_json: &str,
 ) -> Result<String, JsValue> {
-    policy::evaluate_policy_impl(policy_json, tool_name, target.as_deref(), entity_trust_json)
+    policy::evaluate_policy_batch_impl(policy_json, calls_json, entity_trust_json)
+}
+
+/// Compile a policy once for cheap repeated evaluation
+///
+/// Parses, priority-sorts, and indexes the policy, returning an opaque handle.
+/// A long-running host compiles a policy once and passes the handle to
+/// [`evaluate_compiled_policy`] for each tool call, then calls [`free_policy`]
+/// when done.
+///
+/// # Arguments
+/// * `policy_json` - Policy configuration as JSON
+///
+/// # Returns
+/// * A non-zero handle identifying the compiled policy
+#[wasm_bindgen]
+pub fn compile_policy(policy_json: &str) -> Result<u32, JsValue> {
+    policy::compile_policy_impl(policy_json)
+}
+
+/// Evaluate a tool call against a compiled policy handle
+///
+/// # Arguments
+/// * `handle` - A handle returned by [`compile_policy`]
+/// * `tool_name` - Name of the tool being called
+/// * `target` - Optional target (file path, URL, etc.)
+/// * `entity_trust_json` - Entity trust state as JSON
+///
+/// # Returns
+/// * Policy evaluation result as JSON
+#[wasm_bindgen]
+pub fn evaluate_compiled_policy(
+    handle: u32,
+    tool_name: &str,
+    target: Option<String>,
+    entity_trust_json: &str,
+) -> Result<String, JsValue> {
+    policy::evaluate_compiled_impl(handle, tool_name, target.as_deref(), entity_trust_json)
+}
+
+/// Release a compiled policy handle
+///
+/// # Arguments
+/// * `handle` - A handle returned by [`compile_policy`]
+///
+/// # Returns
+/// * `true` if the handle was live, `false` if it was already freed or unknown
+#[wasm_bindgen]
+pub fn free_policy(handle: u32) -> bool {
+    policy::free_policy_impl(handle)
+}
+
+/// Evaluate a tool call and return a per-rule trace for debugging
+///
+/// # Arguments
+/// * `policy_json` - Policy configuration as JSON
+/// * `tool_name` - Name of the tool being called
+/// * `target` - Optional target (file path, URL, etc.)
+/// * `entity_trust_json` - Entity trust state as JSON
+///
+/// # Returns
+/// * JSON `{ "evaluation": ..., "trace": [...] }` recording, for each rule
+///   examined in priority order, whether it matched and which sub-check failed
+#[wasm_bindgen]
+pub fn explain_policy(
+    policy_json: &str,
+    tool_name: &str,
+    target: Option<String>,
+    entity_trust_json: &str,
+) -> Result<String, JsValue> {
+    policy::explain_policy_impl(policy_json, tool_name, target.as_deref(), entity_trust_json)
+}
+
+/// Evaluate a tool call against a compact-binary policy
+///
+/// Takes JS `Uint8Array`s instead of JSON strings, removing the UTF-8 + JSON
+/// overhead from the per-tool-call hot path. Blobs use the SCALE-style layout
+/// from the `codec` module and are decoded on every call. Unlike
+/// [`evaluate_policy`], it takes no rate-limiter state, so aggregate rules
+/// fail closed.
+///
+/// # Arguments
+/// * `policy` - Policy configuration encoded with [`codec::Encode`]
+/// * `tool_name` - Name of the tool being called
+/// * `target` - Optional target (file path, URL, etc.)
+/// * `entity_trust` - Entity trust state encoded with [`codec::Encode`]
+///
+/// # Returns
+/// * Encoded `PolicyEvaluation` bytes
+#[wasm_bindgen]
+pub fn evaluate_policy_bin(
+    policy: &[u8],
+    tool_name: &str,
+    target: Option<String>,
+    entity_trust: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    codec::evaluate_policy_bin_impl(policy, tool_name, target.as_deref(), entity_trust)
 }
 
 /// Update entity trust from tool call outcome
@@ -292,6 +518,40 @@ pub fn record_witness(
     witness::record_witness_impl(witness_id, witnessed_id, trust_score)
 }
 
+/// Aggregate independent attestations into a Byzantine-fault-tolerant consensus
+///
+/// # Arguments
+/// * `attestations_json` - In-window `Attestation`s for a target as a JSON array
+/// * `f` - Maximum tolerated malicious witnesses (requires `N >= 3f + 1`)
+/// * `epsilon` - Score delta above which a witness is treated as equivocating
+///
+/// # Returns
+/// * Consensus result as JSON, including excluded and flagged witnesses
+#[wasm_bindgen]
+pub fn aggregate_consensus(
+    attestations_json: &str,
+    f: usize,
+    epsilon: f64,
+) -> Result<String, JsValue> {
+    witness::aggregate_consensus_impl(attestations_json, f, epsilon)
+}
+
+/// Compute graph-wide EigenTrust scores over the witnessing graph
+///
+/// # Arguments
+/// * `events_json` - All witness events (`WitnessEvent`) as a JSON array
+/// * `pretrusted_json` - Optional `{ entity_id: weight }` pre-trusted map as JSON
+///
+/// # Returns
+/// * Converged per-entity trust scores as JSON
+#[wasm_bindgen]
+pub fn compute_global_trust(
+    events_json: &str,
+    pretrusted_json: &str,
+) -> Result<String, JsValue> {
+    witness::compute_global_trust_impl(events_json, pretrusted_json)
+}
+
 /// Append to audit chain
 ///
 /// # Arguments
@@ -308,6 +568,60 @@ pub fn append_audit(
     audit::append_audit_impl(chain_json, action_json)
 }
 
+/// Verify an audit chain end-to-end
+///
+/// # Arguments
+/// * `chain_json` - Chain state as JSON
+/// * `actions_json` - Full ordered list of `R6Action`s as JSON
+///
+/// # Returns
+/// * Verification result as JSON, naming the first offending action on mismatch
+#[wasm_bindgen]
+pub fn verify_audit_chain(chain_json: &str, actions_json: &str) -> Result<String, JsValue> {
+    audit::verify_chain_impl(chain_json, actions_json)
+}
+
+/// Generate a Merkle inclusion proof for a single audit record
+///
+/// # Arguments
+/// * `chain_json` - Chain state as JSON
+/// * `action_id` - The `action_id` to prove
+///
+/// # Returns
+/// * Merkle proof as JSON
+#[wasm_bindgen]
+pub fn generate_audit_proof(chain_json: &str, action_id: &str) -> Result<String, JsValue> {
+    audit::generate_proof_impl(chain_json, action_id)
+}
+
+/// Verify a Merkle inclusion proof
+///
+/// # Arguments
+/// * `proof_json` - Proof as JSON (as produced by `generate_audit_proof`)
+///
+/// # Returns
+/// * `{ valid, computed_root, expected_root }` as JSON
+#[wasm_bindgen]
+pub fn verify_audit_proof(proof_json: &str) -> Result<String, JsValue> {
+    audit::verify_proof_impl(proof_json)
+}
+
+/// Replay a recorded decision trace and verify audit-chain integrity
+///
+/// Re-executes a compact-binary trace (the `codec` layout) against
+/// a fresh audit chain and checks that every entry hash links to its
+/// predecessor.
+///
+/// # Arguments
+/// * `trace` - Compact-binary trace blob
+///
+/// # Returns
+/// * `{ verified, chain, actions }` as JSON
+#[wasm_bindgen]
+pub fn replay_audit(trace: &[u8]) -> Result<String, JsValue> {
+    replay::replay_audit_impl(trace)
+}
+
 /// Check rate limit
 ///
 /// # Arguments
@@ -407,6 +721,28 @@ mod tests {
         // Should decrease from the gains
     }
 
+    #[test]
+    fn test_v3_tensor_composite() {
+        let v3 = V3Tensor::default();
+        // 0.5 * 0.3 + 0.5 * 0.4 + 0.5 * 0.3 = 0.5
+        assert!((v3.composite() - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_overall_trust_blend() {
+        let t3 = T3Tensor { talent: 0.8, training: 0.8, temperament: 0.8 };
+        let v3 = V3Tensor { valuation: 0.4, veracity: 0.4, validity: 0.4 };
+        // 0.8 * 0.6 + 0.4 * 0.4 = 0.48 + 0.16 = 0.64
+        assert!((OverallTrust::combine(&t3, &v3) - 0.64).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_v3_validity_decays_on_failure() {
+        let mut v3 = V3Tensor::default();
+        v3.update_from_outcome(false, false);
+        assert!(v3.validity < 0.5);
+    }
+
     #[test]
     fn test_tool_category_from_name() {
         assert_eq!(ToolCategory::from_tool_name("Read"), ToolCategory::FileRead);
@@ -1,9 +1,12 @@
 //! Policy evaluation module
 
+use core::cell::{Cell, RefCell};
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use crate::{PolicyDecision, PolicyEvaluation, ToolCategory, T3Tensor, TrustLevel};
+use crate::{PolicyDecision, PolicyEvaluation, ToolCategory, T3Tensor, V3Tensor, OverallTrust, TrustLevel};
 use gastown_shared::FxHashMap;
+use gastown_shared::Arena;
+use gastown_shared::arena::ArenaStr;
 
 /// Policy configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +17,11 @@ pub struct PolicyConfig {
     pub enforce: bool,
     pub default_policy: PolicyDecision,
     pub rules: Vec<PolicyRule>,
+    /// RBAC-style role inheritance: each key maps to the parent roles it
+    /// inherits, in the spirit of casbin's `g` grouping rules. Resolved
+    /// transitively (and cycle-safely) into an entity's effective role set.
+    #[serde(default)]
+    pub role_grants: FxHashMap<String, Vec<String>>,
 }
 
 /// Individual policy rule
@@ -23,12 +31,47 @@ pub struct PolicyRule {
     pub name: String,
     pub priority: u32,
     #[serde(rename = "match")]
-    pub match_spec: PolicyMatch,
+    pub match_spec: RuleMatch,
     pub decision: PolicyDecision,
     #[serde(default)]
     pub reason: Option<String>,
 }
 
+/// Maximum nesting depth for a [`Condition`] tree, to bound recursion from
+/// adversarial policy JSON.
+const MAX_CONDITION_DEPTH: usize = 32;
+
+/// A rule's match specification: either a flat [`PolicyMatch`] (the historical
+/// shape, whose fields are ANDed together) or a nested boolean [`Condition`]
+/// tree.
+///
+/// Untagged so existing policies keep working — a bare `match` object with no
+/// `all`/`any`/`not`/`leaf` key deserializes as [`RuleMatch::Flat`], while a
+/// combinator object deserializes as [`RuleMatch::Condition`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RuleMatch {
+    Condition(Condition),
+    Flat(PolicyMatch),
+}
+
+/// Recursive boolean combinator over [`PolicyMatch`] leaves, borrowing the
+/// nested-condition model from json-rules-engine so a rule can express e.g.
+/// "tool is Write AND (target matches /etc/** OR trust < 0.3)".
+///
+/// `All` short-circuits `false` on the first non-match and an empty `All` is
+/// `true`; `Any` short-circuits `true` on the first match and an empty `Any` is
+/// `false`; `Not` inverts its child. Each `Leaf` is evaluated exactly as the
+/// flat [`matches_rule`] does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+    Leaf(PolicyMatch),
+}
+
 /// Rule matching specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyMatch {
@@ -37,13 +80,163 @@ pub struct PolicyMatch {
     #[serde(default)]
     pub categories: Option<Vec<ToolCategory>>,
     #[serde(default)]
-    pub target_patterns: Option<Vec<String>>,
-    #[serde(default)]
-    pub target_patterns_are_regex: bool,
+    pub target_patterns: Option<Vec<TargetMatcher>>,
     #[serde(default)]
     pub rate_limit: Option<RateLimitSpec>,
     #[serde(default)]
     pub min_trust: Option<f64>,
+    /// Required roles; the rule matches only if the entity's effective role set
+    /// contains at least one of these.
+    #[serde(default)]
+    pub roles: Option<Vec<String>>,
+    /// Optional target normalization applied before the operator matchers.
+    #[serde(default)]
+    pub normalize: Option<RegexReplace>,
+    /// Optional stateful condition over recent history for this entity+tool.
+    #[serde(default)]
+    pub aggregate: Option<AggregateCondition>,
+}
+
+/// Target-matching operator.
+///
+/// Modeled on pact's matching rules and garage's S3 POST `Operation` enum, so
+/// policy authors get precise, predictable target matching instead of the old
+/// regex/glob boolean that routed "regex" patterns to a substring `contains`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchOp {
+    Equal,
+    StartsWith,
+    EndsWith,
+    Contains,
+    Glob,
+    Regex,
+}
+
+/// A single target matcher: an operator applied to a literal value.
+///
+/// Deserializes from either `{ "op": "...", "value": "..." }` or, for
+/// backwards compatibility, a bare string — which is treated as a `glob`
+/// pattern, matching the pre-operator default.
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetMatcher {
+    pub op: MatchOp,
+    pub value: String,
+}
+
+impl<'de> Deserialize<'de> for TargetMatcher {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Bare(String),
+            Typed { op: MatchOp, value: String },
+        }
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Bare(value) => TargetMatcher { op: MatchOp::Glob, value },
+            Raw::Typed { op, value } => TargetMatcher { op, value },
+        })
+    }
+}
+
+impl TargetMatcher {
+    /// Whether this matcher accepts `target`.
+    #[inline]
+    fn matches(&self, target: &str) -> bool {
+        match self.op {
+            MatchOp::Equal => target == self.value,
+            MatchOp::StartsWith => target.starts_with(&self.value),
+            MatchOp::EndsWith => target.ends_with(&self.value),
+            MatchOp::Contains => target.contains(&self.value),
+            MatchOp::Glob => glob_match(&self.value, target),
+            MatchOp::Regex => regex_match(&self.value, target),
+        }
+    }
+}
+
+/// A regex find-and-replace applied to the target before matching, letting a
+/// rule canonicalize paths (e.g. collapse `/home/*/secrets` to a fixed form)
+/// so the operator matchers compare against a normalized value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegexReplace {
+    pub pattern: String,
+    pub replacement: String,
+}
+
+/// Which recent-history counter an [`AggregateCondition`] reads from the
+/// [`RateLimiterState`] windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateMetric {
+    RecentCalls,
+    RecentDenies,
+}
+
+/// Comparison operator for an aggregate threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+}
+
+impl CompareOp {
+    #[inline]
+    fn compare(self, lhs: u32, rhs: u32) -> bool {
+        match self {
+            CompareOp::Gt => lhs > rhs,
+            CompareOp::Gte => lhs >= rhs,
+            CompareOp::Lt => lhs < rhs,
+            CompareOp::Lte => lhs <= rhs,
+            CompareOp::Eq => lhs == rhs,
+        }
+    }
+}
+
+/// A stateful condition that makes a rule depend on recent history for the
+/// entity+tool key — e.g. `recent_denies(60000) >= 3` — rather than only the
+/// current call. Evaluated against the same timestamp windows the rate limiter
+/// maintains, pruning entries older than `now - window_ms` before counting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateCondition {
+    pub metric: AggregateMetric,
+    pub op: CompareOp,
+    pub window_ms: u64,
+    pub threshold: u32,
+}
+
+/// Read-only context for evaluating [`AggregateCondition`]s against recent
+/// history. Only the stateful entry point ([`evaluate_policy_impl`]) supplies
+/// one; the pure [`evaluate`] path passes `None`, so aggregate rules fail
+/// closed there.
+struct AggregateCtx<'a> {
+    now: u64,
+    state: &'a RateLimiterState,
+    entity_id: &'a str,
+}
+
+impl AggregateCondition {
+    /// Count the matching timestamps in the window and apply the comparison.
+    fn satisfied(&self, ctx: &AggregateCtx, tool_name: &str) -> bool {
+        let key = match self.metric {
+            AggregateMetric::RecentCalls => format!("{}:{}", ctx.entity_id, tool_name),
+            AggregateMetric::RecentDenies => format!("{}:{}:deny", ctx.entity_id, tool_name),
+        };
+        let window_start = ctx.now.saturating_sub(self.window_ms);
+        let count = ctx
+            .state
+            .windows
+            .get(&key)
+            .map(|ts| ts.iter().filter(|&&t| t > window_start).count())
+            .unwrap_or(0) as u32;
+        self.op.compare(count, self.threshold)
+    }
 }
 
 /// Rate limit specification
@@ -58,8 +251,15 @@ pub struct RateLimitSpec {
 pub struct EntityTrust {
     pub entity_id: String,
     pub t3: T3Tensor,
+    /// Accrued value tensor; absent for entities that predate V3 tracking.
+    #[serde(default)]
+    pub v3: Option<V3Tensor>,
     #[serde(default)]
     pub interaction_count: u64,
+    /// Roles declared for this entity; expanded transitively through the
+    /// policy's `role_grants` into the effective role set used for matching.
+    #[serde(default)]
+    pub roles: Vec<String>,
 }
 
 /// Rate limiter state
@@ -77,13 +277,161 @@ pub struct RateLimitResult {
     pub reset_in_ms: u64,
 }
 
-/// Evaluate policy against a tool call
+/// Evaluate policy against a tool call, threading the rate-limiter state so
+/// stateful ([`AggregateCondition`]) rules can read recent history.
+///
+/// Returns a combined JSON object `{ "evaluation": ..., "state": ... }`: the
+/// state carries any mutations (this call is recorded into the entity+tool
+/// window, and a deny is additionally recorded into the deny window) so the
+/// host can feed it back on the next call. A missing or invalid `state_json`
+/// starts from an empty window set, matching [`check_rate_limit_impl`].
+///
+/// This is the one-shot path: it compiles the policy (see [`CompiledPolicy`])
+/// and evaluates once. A host making many calls should instead compile once via
+/// [`compile_policy_impl`] and reuse the handle.
 #[inline]
 pub fn evaluate_policy_impl(
     policy_json: &str,
     tool_name: &str,
     target: Option<&str>,
     entity_trust_json: &str,
+    state_json: &str,
+) -> Result<String, JsValue> {
+    let compiled = CompiledPolicy::compile(policy_json)?;
+
+    let entity: EntityTrust = serde_json::from_str(entity_trust_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid entity trust JSON: {}", e)))?;
+
+    let mut state: RateLimiterState = serde_json::from_str(state_json)
+        .unwrap_or_else(|_| RateLimiterState { windows: FxHashMap::default() });
+
+    let now = js_sys::Date::now() as u64;
+    let result = {
+        let ctx = AggregateCtx { now, state: &state, entity_id: &entity.entity_id };
+        compiled.evaluate(tool_name, target, &entity, Some(&ctx))
+    };
+    record_call(
+        &mut state,
+        &entity.entity_id,
+        tool_name,
+        now,
+        result.decision,
+        compiled.max_aggregate_window_ms,
+    );
+
+    let response = serde_json::json!({ "evaluation": result, "state": state });
+    serde_json::to_string(&response)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// One tool call in a batch request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchCall {
+    pub tool_name: String,
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// A single call's decision within a [`BatchReport`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCallReport {
+    pub tool_name: String,
+    pub target: Option<String>,
+    pub evaluation: PolicyEvaluation,
+}
+
+/// Combined report for a batch of tool calls evaluated against one policy.
+///
+/// Carries the per-call decisions plus roll-up aggregates so a host can
+/// validate an entire planned action sequence in a single WASM call and keep
+/// one artifact for logging: total allowed/denied/warn counts, how many times
+/// each rule fired, and the indices of calls that fell through to the default
+/// policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub calls: Vec<BatchCallReport>,
+    pub total_allowed: u32,
+    pub total_denied: u32,
+    pub total_warn: u32,
+    pub rule_fire_counts: FxHashMap<String, u32>,
+    pub default_policy_calls: Vec<usize>,
+}
+
+/// Evaluate a sequence of tool calls against one policy, returning a combined
+/// [`BatchReport`].
+///
+/// Reuses the same priority-sorting and [`matches_rule`] path as the
+/// single-call [`evaluate`]; this is the stateless path, so aggregate rules
+/// fail closed. `calls_json` is an array of `{ tool_name, target }` objects.
+pub fn evaluate_policy_batch_impl(
+    policy_json: &str,
+    calls_json: &str,
+    entity_trust_json: &str,
+) -> Result<String, JsValue> {
+    let policy: PolicyConfig = serde_json::from_str(policy_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid policy JSON: {}", e)))?;
+
+    let calls: Vec<BatchCall> = serde_json::from_str(calls_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid calls JSON: {}", e)))?;
+
+    let entity: EntityTrust = serde_json::from_str(entity_trust_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid entity trust JSON: {}", e)))?;
+
+    let mut report = BatchReport {
+        calls: Vec::with_capacity(calls.len()),
+        total_allowed: 0,
+        total_denied: 0,
+        total_warn: 0,
+        rule_fire_counts: FxHashMap::default(),
+        default_policy_calls: Vec::new(),
+    };
+
+    for (idx, call) in calls.into_iter().enumerate() {
+        let eval = evaluate(&policy, &call.tool_name, call.target.as_deref(), &entity);
+        match eval.decision {
+            PolicyDecision::Allow => report.total_allowed += 1,
+            PolicyDecision::Deny => report.total_denied += 1,
+            PolicyDecision::AskUser | PolicyDecision::LogOnly => report.total_warn += 1,
+        }
+        match &eval.matched_rule {
+            Some(id) => *report.rule_fire_counts.entry(id.clone()).or_insert(0) += 1,
+            None => report.default_policy_calls.push(idx),
+        }
+        report.calls.push(BatchCallReport {
+            tool_name: call.tool_name,
+            target: call.target,
+            evaluation: eval,
+        });
+    }
+
+    serde_json::to_string(&report)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// One rule's outcome in an explain trace: whether it matched and, if not,
+/// which sub-check rejected the call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTrace {
+    pub rule_id: String,
+    pub matched: bool,
+    #[serde(default)]
+    pub failed_check: Option<FailedCheck>,
+}
+
+/// Evaluate a tool call and additionally return a per-rule trace, for debugging
+/// why a given rule did or did not fire.
+///
+/// Returns `{ "evaluation": ..., "trace": [RuleTrace, ...] }`. The trace lists
+/// every rule examined in priority order up to and including the one that
+/// matched (evaluation short-circuits there, so later rules are never
+/// examined). The authoritative decision comes from [`evaluate`], leaving the
+/// fast non-explain path untouched. This is the stateless path, so aggregate
+/// rules fail closed.
+pub fn explain_policy_impl(
+    policy_json: &str,
+    tool_name: &str,
+    target: Option<&str>,
+    entity_trust_json: &str,
 ) -> Result<String, JsValue> {
     let policy: PolicyConfig = serde_json::from_str(policy_json)
         .map_err(|e| JsValue::from_str(&format!("Invalid policy JSON: {}", e)))?;
@@ -92,34 +440,129 @@ pub fn evaluate_policy_impl(
         .map_err(|e| JsValue::from_str(&format!("Invalid entity trust JSON: {}", e)))?;
 
     let category = ToolCategory::from_tool_name(tool_name);
-    let trust_score = entity.t3.composite();
+    let trust_score = match &entity.v3 {
+        Some(v3) => OverallTrust::combine(&entity.t3, v3),
+        None => entity.t3.composite(),
+    };
+    let roles = effective_roles(&entity.roles, &policy.role_grants);
+
+    let mut rules = policy.rules.clone();
+    rules.sort_by_key(|r| r.priority);
+
+    let mut trace = Vec::new();
+    for rule in &rules {
+        let failed = rule
+            .match_spec
+            .explain(tool_name, category, target, trust_score, &roles, None);
+        let matched = failed.is_none();
+        trace.push(RuleTrace {
+            rule_id: rule.id.clone(),
+            matched,
+            failed_check: failed,
+        });
+        // Evaluation stops at the first matching rule; later rules are not
+        // examined, so they do not appear in the trace.
+        if matched {
+            break;
+        }
+    }
+
+    let evaluation = evaluate(&policy, tool_name, target, &entity);
+    let response = serde_json::json!({ "evaluation": evaluation, "trace": trace });
+    serde_json::to_string(&response)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// Record this call into the entity+tool window, and a deny into the deny
+/// window, so later [`AggregateCondition`]s can count it.
+///
+/// `retention_ms` is the longest aggregate window any rule declares: timestamps
+/// older than that are never read again, so each touched window is pruned to it
+/// on insert (matching the rate-limiter's `retain` semantics) and cannot grow
+/// without bound on a long-lived host.
+fn record_call(
+    state: &mut RateLimiterState,
+    entity_id: &str,
+    tool_name: &str,
+    now: u64,
+    decision: PolicyDecision,
+    retention_ms: u64,
+) {
+    let horizon = now.saturating_sub(retention_ms);
+    let mut record = |key: String| {
+        let window = state.windows.entry(key).or_default();
+        window.retain(|&ts| ts > horizon);
+        window.push(now);
+    };
+    record(format!("{}:{}", entity_id, tool_name));
+    if decision == PolicyDecision::Deny {
+        record(format!("{}:{}:deny", entity_id, tool_name));
+    }
+}
+
+/// Core policy evaluation, independent of the serialization at the boundary.
+///
+/// Used by the compact-binary entry point
+/// ([`crate::codec::evaluate_policy_bin_impl`]); the JSON
+/// [`evaluate_policy_impl`] evaluates through a `CompiledPolicy` instead. This
+/// is the stateless path: aggregate rules fail closed because no history is
+/// supplied.
+pub fn evaluate(
+    policy: &PolicyConfig,
+    tool_name: &str,
+    target: Option<&str>,
+    entity: &EntityTrust,
+) -> PolicyEvaluation {
+    evaluate_with_state(policy, tool_name, target, entity, None)
+}
+
+/// Shared evaluation core, optionally threading aggregate history.
+fn evaluate_with_state(
+    policy: &PolicyConfig,
+    tool_name: &str,
+    target: Option<&str>,
+    entity: &EntityTrust,
+    agg: Option<&AggregateCtx>,
+) -> PolicyEvaluation {
+    let category = ToolCategory::from_tool_name(tool_name);
+    // Gate on both capability trust (T3) and accrued value (V3) when the entity
+    // carries a value tensor; fall back to T3 alone otherwise.
+    let trust_score = match &entity.v3 {
+        Some(v3) => OverallTrust::combine(&entity.t3, v3),
+        None => entity.t3.composite(),
+    };
+
+    // Expand the entity's declared roles transitively for role-constrained rules.
+    let roles = effective_roles(&entity.roles, &policy.role_grants);
 
     // Sort rules by priority (lower = evaluated first)
     let mut rules = policy.rules.clone();
     rules.sort_by_key(|r| r.priority);
 
     for rule in &rules {
-        if matches_rule(tool_name, category, target, &rule.match_spec, trust_score) {
+        if rule.match_spec.matches(tool_name, category, target, trust_score, &roles, agg) {
             let enforced = rule.decision != PolicyDecision::Deny || policy.enforce;
-            let result = PolicyEvaluation {
+            let mut constraints = vec![
+                format!("policy:{}", policy.name),
+                format!("rule:{}", rule.id),
+                format!("decision:{:?}", rule.decision),
+            ];
+            if !roles.is_empty() {
+                constraints.push(format!("effective_roles:{}", roles.join(",")));
+            }
+            return PolicyEvaluation {
                 decision: rule.decision,
                 matched_rule: Some(rule.id.clone()),
                 enforced,
                 reason: rule.reason.clone().unwrap_or_else(|| format!("Matched rule: {}", rule.name)),
                 trust_score,
-                constraints: vec![
-                    format!("policy:{}", policy.name),
-                    format!("rule:{}", rule.id),
-                    format!("decision:{:?}", rule.decision),
-                ],
+                constraints,
             };
-            return serde_json::to_string(&result)
-                .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)));
         }
     }
 
     // No rule matched - use default policy
-    let result = PolicyEvaluation {
+    PolicyEvaluation {
         decision: policy.default_policy,
         matched_rule: None,
         enforced: true,
@@ -130,64 +573,769 @@ pub fn evaluate_policy_impl(
             "rule:default".to_string(),
             format!("decision:{:?}", policy.default_policy),
         ],
-    };
+    }
+}
 
-    serde_json::to_string(&result)
-        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+/// Borrowing policy evaluation backed by a [`PolicyEngine`]'s arena.
+///
+/// The rule id, reason, and constraint strings are `ArenaStr` slices into the
+/// engine's bump allocator rather than owned `String`s. The `constraints` `Vec`
+/// itself, role expansion, and target matching still allocate on the heap.
+#[derive(Debug, Clone)]
+pub struct PolicyEvaluationRef<'a> {
+    pub decision: PolicyDecision,
+    pub matched_rule: Option<ArenaStr<'a>>,
+    pub enforced: bool,
+    pub reason: ArenaStr<'a>,
+    pub trust_score: f64,
+    pub constraints: Vec<ArenaStr<'a>>,
+}
+
+impl<'a> PolicyEvaluationRef<'a> {
+    /// Materialize the borrowing evaluation into an owned [`PolicyEvaluation`]
+    /// for the JSON / WASM boundary.
+    pub fn to_owned(&self) -> PolicyEvaluation {
+        PolicyEvaluation {
+            decision: self.decision,
+            matched_rule: self.matched_rule.map(|s| s.as_str().to_string()),
+            enforced: self.enforced,
+            reason: self.reason.as_str().to_string(),
+            trust_score: self.trust_score,
+            constraints: self.constraints.iter().map(|s| s.as_str().to_string()).collect(),
+        }
+    }
+}
+
+/// A policy parsed and priority-sorted once, paired with an arena that holds
+/// the result strings of repeated evaluations.
+///
+/// A long-running host constructs one `PolicyEngine` per policy and evaluates
+/// many tool calls against it, calling [`PolicyEngine::reset`] between batches
+/// to reclaim arena memory. The owned/JSON [`evaluate_policy_impl`] does not
+/// use an engine; it compiles a `CompiledPolicy` and evaluates through that.
+pub struct PolicyEngine {
+    arena: Arena,
+    policy: PolicyConfig,
+}
+
+impl PolicyEngine {
+    /// Parse a policy once and pre-sort its rules by priority.
+    pub fn new(policy_json: &str) -> Result<Self, JsValue> {
+        let mut policy: PolicyConfig = serde_json::from_str(policy_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid policy JSON: {}", e)))?;
+        policy.rules.sort_by_key(|r| r.priority);
+        Ok(Self { arena: Arena::new(), policy })
+    }
+
+    /// Reset the arena between batches of tool calls (O(1)).
+    ///
+    /// Takes `&mut self` so an outstanding [`PolicyEvaluationRef`] — whose
+    /// `ArenaStr` fields borrow `self.arena` — keeps the engine immutably
+    /// borrowed and makes a reset that would free those bytes a compile error.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.arena.reset();
+    }
+
+    /// Evaluate a tool call, producing an arena-backed borrowing result.
+    pub fn evaluate<'a>(
+        &'a self,
+        tool_name: &str,
+        target: Option<&str>,
+        entity: &EntityTrust,
+    ) -> PolicyEvaluationRef<'a> {
+        let category = ToolCategory::from_tool_name(tool_name);
+        let trust_score = match &entity.v3 {
+            Some(v3) => crate::OverallTrust::combine(&entity.t3, v3),
+            None => entity.t3.composite(),
+        };
+        // `intern` copies a borrowed slice in; `fmt` builds a composed string
+        // straight into the arena. Neither routes through an owned `String`
+        // temporary for the per-field strings. Role expansion below, the
+        // `roles.join`, and the `constraints` Vec still allocate on the heap.
+        let intern = |s: &str| ArenaStr::new(&self.arena, s);
+        let fmt = |args: std::fmt::Arguments| ArenaStr::from_arena(self.arena.alloc_fmt(args));
+        let roles = effective_roles(&entity.roles, &self.policy.role_grants);
+
+        // Rules are already priority-sorted at construction time.
+        for rule in &self.policy.rules {
+            if rule.match_spec.matches(tool_name, category, target, trust_score, &roles, None) {
+                let enforced = rule.decision != PolicyDecision::Deny || self.policy.enforce;
+                let reason = match &rule.reason {
+                    Some(r) => intern(r),
+                    None => fmt(format_args!("Matched rule: {}", rule.name)),
+                };
+                let mut constraints = vec![
+                    fmt(format_args!("policy:{}", self.policy.name)),
+                    fmt(format_args!("rule:{}", rule.id)),
+                    fmt(format_args!("decision:{:?}", rule.decision)),
+                ];
+                if !roles.is_empty() {
+                    constraints.push(fmt(format_args!("effective_roles:{}", roles.join(","))));
+                }
+                return PolicyEvaluationRef {
+                    decision: rule.decision,
+                    matched_rule: Some(intern(&rule.id)),
+                    enforced,
+                    reason,
+                    trust_score,
+                    constraints,
+                };
+            }
+        }
+
+        PolicyEvaluationRef {
+            decision: self.policy.default_policy,
+            matched_rule: None,
+            enforced: true,
+            reason: fmt(format_args!("Default policy: {:?}", self.policy.default_policy)),
+            trust_score,
+            constraints: vec![
+                fmt(format_args!("policy:{}", self.policy.name)),
+                intern("rule:default"),
+                fmt(format_args!("decision:{:?}", self.policy.default_policy)),
+            ],
+        }
+    }
+}
+
+/// Expand an entity's declared roles into the full set reachable through
+/// `grants`. The visited set doubles as cycle protection, so a self- or
+/// mutually-referential grant cannot loop forever.
+fn effective_roles(declared: &[String], grants: &FxHashMap<String, Vec<String>>) -> Vec<String> {
+    let mut effective: Vec<String> = Vec::new();
+    let mut stack: Vec<String> = declared.to_vec();
+    while let Some(role) = stack.pop() {
+        if effective.iter().any(|r| r == &role) {
+            continue;
+        }
+        if let Some(parents) = grants.get(&role) {
+            for parent in parents {
+                stack.push(parent.clone());
+            }
+        }
+        effective.push(role);
+    }
+    effective
+}
+
+// ----------------------------------------------------------------------------
+// Compiled policy
+//
+// `evaluate_policy_impl` re-parses the policy JSON, clones the rule vector, and
+// re-sorts by priority on every call. A long-running host instead compiles a
+// policy once into a `CompiledPolicy` — rules priority-sorted, every matcher
+// pre-parsed into its executable form, and lookup indexes built by tool name
+// and category — then evaluates many calls against the handle, scanning only
+// the rules that could match the incoming tool/category.
+// ----------------------------------------------------------------------------
+
+/// A target matcher with any regex pattern parsed ahead of time.
+enum CompiledMatcher {
+    Equal(String),
+    StartsWith(String),
+    EndsWith(String),
+    Contains(String),
+    Glob(String),
+    Regex(CompiledRegex),
+}
+
+impl CompiledMatcher {
+    fn compile(m: &TargetMatcher) -> Self {
+        match m.op {
+            MatchOp::Equal => CompiledMatcher::Equal(m.value.clone()),
+            MatchOp::StartsWith => CompiledMatcher::StartsWith(m.value.clone()),
+            MatchOp::EndsWith => CompiledMatcher::EndsWith(m.value.clone()),
+            MatchOp::Contains => CompiledMatcher::Contains(m.value.clone()),
+            MatchOp::Glob => CompiledMatcher::Glob(m.value.clone()),
+            MatchOp::Regex => CompiledMatcher::Regex(CompiledRegex::compile(&m.value)),
+        }
+    }
+
+    #[inline]
+    fn matches(&self, target: &str) -> bool {
+        match self {
+            CompiledMatcher::Equal(v) => target == v,
+            CompiledMatcher::StartsWith(v) => target.starts_with(v),
+            CompiledMatcher::EndsWith(v) => target.ends_with(v),
+            CompiledMatcher::Contains(v) => target.contains(v),
+            CompiledMatcher::Glob(v) => glob_match(v, target),
+            CompiledMatcher::Regex(re) => re.is_match(target),
+        }
+    }
+}
+
+/// A flat [`PolicyMatch`] with its matchers pre-compiled.
+struct CompiledMatch {
+    tools: Option<Vec<String>>,
+    categories: Option<Vec<ToolCategory>>,
+    target_patterns: Option<Vec<CompiledMatcher>>,
+    min_trust: Option<f64>,
+    roles: Option<Vec<String>>,
+    normalize: Option<(CompiledRegex, String)>,
+    aggregate: Option<AggregateCondition>,
+}
+
+impl CompiledMatch {
+    fn compile(spec: &PolicyMatch) -> Self {
+        CompiledMatch {
+            tools: spec.tools.clone(),
+            categories: spec.categories.clone(),
+            target_patterns: spec
+                .target_patterns
+                .as_ref()
+                .map(|ms| ms.iter().map(CompiledMatcher::compile).collect()),
+            min_trust: spec.min_trust,
+            roles: spec.roles.clone(),
+            normalize: spec
+                .normalize
+                .as_ref()
+                .map(|n| (CompiledRegex::compile(&n.pattern), n.replacement.clone())),
+            aggregate: spec.aggregate.clone(),
+        }
+    }
+
+    /// Mirror of [`check_rule`] over the pre-compiled representation.
+    #[allow(clippy::too_many_arguments)]
+    fn matches(
+        &self,
+        tool_name: &str,
+        category: ToolCategory,
+        target: Option<&str>,
+        trust_score: f64,
+        roles: &[String],
+        agg: Option<&AggregateCtx>,
+    ) -> bool {
+        if let Some(min_trust) = self.min_trust {
+            if trust_score < min_trust {
+                return false;
+            }
+        }
+        if let Some(required) = &self.roles {
+            if !required.iter().any(|r| roles.iter().any(|e| e == r)) {
+                return false;
+            }
+        }
+        if let Some(cond) = &self.aggregate {
+            match agg {
+                Some(ctx) if cond.satisfied(ctx, tool_name) => {}
+                _ => return false,
+            }
+        }
+        if let Some(tools) = &self.tools {
+            if !tools.iter().any(|t| t == tool_name) {
+                return false;
+            }
+        }
+        if let Some(categories) = &self.categories {
+            if !categories.contains(&category) {
+                return false;
+            }
+        }
+        if let Some(matchers) = &self.target_patterns {
+            let Some(target) = target else {
+                return false;
+            };
+            let normalized = match &self.normalize {
+                Some((re, repl)) => re.replace_first(repl, target),
+                None => target.to_string(),
+            };
+            if !matchers.iter().any(|m| m.matches(&normalized)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Pre-compiled counterpart of [`RuleMatch`].
+enum CompiledRuleMatch {
+    Flat(CompiledMatch),
+    Condition(CompiledCondition),
+}
+
+/// Pre-compiled counterpart of [`Condition`].
+enum CompiledCondition {
+    All(Vec<CompiledCondition>),
+    Any(Vec<CompiledCondition>),
+    Not(Box<CompiledCondition>),
+    Leaf(CompiledMatch),
+}
+
+impl CompiledCondition {
+    fn compile(cond: &Condition) -> Self {
+        match cond {
+            Condition::All(cs) => CompiledCondition::All(cs.iter().map(Self::compile).collect()),
+            Condition::Any(cs) => CompiledCondition::Any(cs.iter().map(Self::compile).collect()),
+            Condition::Not(c) => CompiledCondition::Not(Box::new(Self::compile(c))),
+            Condition::Leaf(spec) => CompiledCondition::Leaf(CompiledMatch::compile(spec)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn eval(
+        &self,
+        depth: usize,
+        tool_name: &str,
+        category: ToolCategory,
+        target: Option<&str>,
+        trust_score: f64,
+        roles: &[String],
+        agg: Option<&AggregateCtx>,
+    ) -> bool {
+        if depth > MAX_CONDITION_DEPTH {
+            return false;
+        }
+        match self {
+            CompiledCondition::All(cs) => cs
+                .iter()
+                .all(|c| c.eval(depth + 1, tool_name, category, target, trust_score, roles, agg)),
+            CompiledCondition::Any(cs) => cs
+                .iter()
+                .any(|c| c.eval(depth + 1, tool_name, category, target, trust_score, roles, agg)),
+            CompiledCondition::Not(c) => {
+                !c.eval(depth + 1, tool_name, category, target, trust_score, roles, agg)
+            }
+            CompiledCondition::Leaf(spec) => {
+                spec.matches(tool_name, category, target, trust_score, roles, agg)
+            }
+        }
+    }
+}
+
+impl CompiledRuleMatch {
+    fn compile(spec: &RuleMatch) -> Self {
+        match spec {
+            RuleMatch::Flat(m) => CompiledRuleMatch::Flat(CompiledMatch::compile(m)),
+            RuleMatch::Condition(c) => CompiledRuleMatch::Condition(CompiledCondition::compile(c)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn matches(
+        &self,
+        tool_name: &str,
+        category: ToolCategory,
+        target: Option<&str>,
+        trust_score: f64,
+        roles: &[String],
+        agg: Option<&AggregateCtx>,
+    ) -> bool {
+        match self {
+            CompiledRuleMatch::Flat(m) => {
+                m.matches(tool_name, category, target, trust_score, roles, agg)
+            }
+            CompiledRuleMatch::Condition(c) => {
+                c.eval(0, tool_name, category, target, trust_score, roles, agg)
+            }
+        }
+    }
+}
+
+/// A single compiled rule, carrying the decision metadata alongside its
+/// pre-compiled match spec.
+struct CompiledRule {
+    id: String,
+    name: String,
+    decision: PolicyDecision,
+    reason: Option<String>,
+    match_spec: CompiledRuleMatch,
+}
+
+/// A policy parsed, priority-sorted, matcher-compiled, and indexed once for
+/// cheap repeated evaluation.
+///
+/// The `by_tool` / `by_category` indexes and the `any_tool` bucket hold rule
+/// indexes into the priority-sorted `rules`, so evaluation scans only the rules
+/// that could match the incoming tool or category rather than the whole set.
+pub struct CompiledPolicy {
+    name: String,
+    enforce: bool,
+    default_policy: PolicyDecision,
+    role_grants: FxHashMap<String, Vec<String>>,
+    rules: Vec<CompiledRule>,
+    by_tool: FxHashMap<String, Vec<usize>>,
+    by_category: FxHashMap<ToolCategory, Vec<usize>>,
+    any_tool: Vec<usize>,
+    /// Longest aggregate window declared by any rule. History older than this
+    /// is never read, so `record_call` prunes to it on insert.
+    max_aggregate_window_ms: u64,
+}
+
+/// The longest aggregate window declared anywhere in a rule's match spec, or 0
+/// if it has no aggregate conditions.
+fn max_aggregate_window(spec: &RuleMatch) -> u64 {
+    fn in_condition(cond: &Condition) -> u64 {
+        match cond {
+            Condition::All(cs) | Condition::Any(cs) => {
+                cs.iter().map(in_condition).max().unwrap_or(0)
+            }
+            Condition::Not(c) => in_condition(c),
+            Condition::Leaf(m) => m.aggregate.as_ref().map_or(0, |a| a.window_ms),
+        }
+    }
+    match spec {
+        RuleMatch::Flat(m) => m.aggregate.as_ref().map_or(0, |a| a.window_ms),
+        RuleMatch::Condition(c) => in_condition(c),
+    }
+}
+
+impl CompiledPolicy {
+    /// Parse, sort, compile, and index a policy from JSON.
+    pub fn compile(policy_json: &str) -> Result<Self, JsValue> {
+        let policy: PolicyConfig = serde_json::from_str(policy_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid policy JSON: {}", e)))?;
+
+        let mut sorted = policy.rules;
+        sorted.sort_by_key(|r| r.priority);
+
+        let mut rules = Vec::with_capacity(sorted.len());
+        let mut by_tool: FxHashMap<String, Vec<usize>> = FxHashMap::default();
+        let mut by_category: FxHashMap<ToolCategory, Vec<usize>> = FxHashMap::default();
+        let mut any_tool: Vec<usize> = Vec::new();
+        let mut max_aggregate_window_ms: u64 = 0;
+
+        for (idx, rule) in sorted.iter().enumerate() {
+            max_aggregate_window_ms =
+                max_aggregate_window_ms.max(max_aggregate_window(&rule.match_spec));
+            // A rule is reachable for the incoming call only through the index
+            // its top-level constraints allow; an unconstrained flat rule or a
+            // condition tree goes in the any-tool bucket so it is always scanned.
+            match &rule.match_spec {
+                RuleMatch::Flat(m) if m.tools.is_some() => {
+                    for tool in m.tools.as_ref().unwrap() {
+                        by_tool.entry(tool.clone()).or_default().push(idx);
+                    }
+                }
+                RuleMatch::Flat(m) if m.categories.is_some() => {
+                    for cat in m.categories.as_ref().unwrap() {
+                        by_category.entry(*cat).or_default().push(idx);
+                    }
+                }
+                _ => any_tool.push(idx),
+            }
+
+            rules.push(CompiledRule {
+                id: rule.id.clone(),
+                name: rule.name.clone(),
+                decision: rule.decision,
+                reason: rule.reason.clone(),
+                match_spec: CompiledRuleMatch::compile(&rule.match_spec),
+            });
+        }
+
+        Ok(CompiledPolicy {
+            name: policy.name,
+            enforce: policy.enforce,
+            default_policy: policy.default_policy,
+            role_grants: policy.role_grants,
+            rules,
+            by_tool,
+            by_category,
+            any_tool,
+            max_aggregate_window_ms,
+        })
+    }
+
+    /// Evaluate a tool call against the compiled policy, scanning only the
+    /// candidate rules for the incoming tool and category.
+    fn evaluate(
+        &self,
+        tool_name: &str,
+        target: Option<&str>,
+        entity: &EntityTrust,
+        agg: Option<&AggregateCtx>,
+    ) -> PolicyEvaluation {
+        let category = ToolCategory::from_tool_name(tool_name);
+        let trust_score = match &entity.v3 {
+            Some(v3) => OverallTrust::combine(&entity.t3, v3),
+            None => entity.t3.composite(),
+        };
+        let roles = effective_roles(&entity.roles, &self.role_grants);
+
+        // Merge the candidate index lists. The three buckets are disjoint and
+        // each ascending, so the merged scan stays in priority order.
+        let mut candidates: Vec<usize> = Vec::new();
+        if let Some(idxs) = self.by_tool.get(tool_name) {
+            candidates.extend_from_slice(idxs);
+        }
+        if let Some(idxs) = self.by_category.get(&category) {
+            candidates.extend_from_slice(idxs);
+        }
+        candidates.extend_from_slice(&self.any_tool);
+        candidates.sort_unstable();
+
+        for &idx in &candidates {
+            let rule = &self.rules[idx];
+            if rule.match_spec.matches(tool_name, category, target, trust_score, &roles, agg) {
+                let enforced = rule.decision != PolicyDecision::Deny || self.enforce;
+                let mut constraints = vec![
+                    format!("policy:{}", self.name),
+                    format!("rule:{}", rule.id),
+                    format!("decision:{:?}", rule.decision),
+                ];
+                if !roles.is_empty() {
+                    constraints.push(format!("effective_roles:{}", roles.join(",")));
+                }
+                return PolicyEvaluation {
+                    decision: rule.decision,
+                    matched_rule: Some(rule.id.clone()),
+                    enforced,
+                    reason: rule
+                        .reason
+                        .clone()
+                        .unwrap_or_else(|| format!("Matched rule: {}", rule.name)),
+                    trust_score,
+                    constraints,
+                };
+            }
+        }
+
+        PolicyEvaluation {
+            decision: self.default_policy,
+            matched_rule: None,
+            enforced: true,
+            reason: format!("Default policy: {:?}", self.default_policy),
+            trust_score,
+            constraints: vec![
+                format!("policy:{}", self.name),
+                "rule:default".to_string(),
+                format!("decision:{:?}", self.default_policy),
+            ],
+        }
+    }
+}
+
+thread_local! {
+    /// Live compiled policies keyed by the handle handed back to the host.
+    static COMPILED: RefCell<FxHashMap<u32, CompiledPolicy>> = RefCell::new(FxHashMap::default());
+    /// Monotonic source of handle ids (0 is reserved as "invalid").
+    static NEXT_HANDLE: Cell<u32> = const { Cell::new(1) };
+}
+
+/// Compile a policy and register it, returning an opaque handle the host keeps
+/// for later [`evaluate_compiled_impl`] / [`free_policy_impl`] calls.
+pub fn compile_policy_impl(policy_json: &str) -> Result<u32, JsValue> {
+    let compiled = CompiledPolicy::compile(policy_json)?;
+    let handle = NEXT_HANDLE.with(|c| {
+        let h = c.get();
+        c.set(h.wrapping_add(1).max(1));
+        h
+    });
+    COMPILED.with(|m| m.borrow_mut().insert(handle, compiled));
+    Ok(handle)
+}
+
+/// Evaluate a tool call against a previously compiled policy handle.
+pub fn evaluate_compiled_impl(
+    handle: u32,
+    tool_name: &str,
+    target: Option<&str>,
+    entity_trust_json: &str,
+) -> Result<String, JsValue> {
+    let entity: EntityTrust = serde_json::from_str(entity_trust_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid entity trust JSON: {}", e)))?;
+
+    COMPILED.with(|m| {
+        let map = m.borrow();
+        let compiled = map
+            .get(&handle)
+            .ok_or_else(|| JsValue::from_str("Unknown policy handle"))?;
+        let eval = compiled.evaluate(tool_name, target, &entity, None);
+        serde_json::to_string(&eval)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    })
+}
+
+/// Release a compiled policy handle, returning whether it was present.
+pub fn free_policy_impl(handle: u32) -> bool {
+    COMPILED.with(|m| m.borrow_mut().remove(&handle).is_some())
+}
+
+impl RuleMatch {
+    /// Evaluate this match spec against a tool call.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    fn matches(
+        &self,
+        tool_name: &str,
+        category: ToolCategory,
+        target: Option<&str>,
+        trust_score: f64,
+        roles: &[String],
+        agg: Option<&AggregateCtx>,
+    ) -> bool {
+        match self {
+            RuleMatch::Flat(spec) => {
+                matches_rule(tool_name, category, target, spec, trust_score, roles, agg)
+            }
+            RuleMatch::Condition(cond) => {
+                eval_condition(cond, 0, tool_name, category, target, trust_score, roles, agg)
+            }
+        }
+    }
+
+    /// Like [`RuleMatch::matches`] but reports the first failing sub-check for
+    /// explain mode. A flat rule delegates to [`check_rule`]; a condition tree
+    /// reports [`FailedCheck::Condition`] as a whole, since its failure is not
+    /// attributable to a single leaf predicate.
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    fn explain(
+        &self,
+        tool_name: &str,
+        category: ToolCategory,
+        target: Option<&str>,
+        trust_score: f64,
+        roles: &[String],
+        agg: Option<&AggregateCtx>,
+    ) -> Option<FailedCheck> {
+        match self {
+            RuleMatch::Flat(spec) => {
+                check_rule(tool_name, category, target, spec, trust_score, roles, agg)
+            }
+            RuleMatch::Condition(cond) => {
+                if eval_condition(cond, 0, tool_name, category, target, trust_score, roles, agg) {
+                    None
+                } else {
+                    Some(FailedCheck::Condition)
+                }
+            }
+        }
+    }
+}
+
+/// Walk a [`Condition`] tree. Trees deeper than [`MAX_CONDITION_DEPTH`] are
+/// rejected (evaluate to `false`) rather than risking stack exhaustion.
+#[allow(clippy::too_many_arguments)]
+fn eval_condition(
+    cond: &Condition,
+    depth: usize,
+    tool_name: &str,
+    category: ToolCategory,
+    target: Option<&str>,
+    trust_score: f64,
+    roles: &[String],
+    agg: Option<&AggregateCtx>,
+) -> bool {
+    if depth > MAX_CONDITION_DEPTH {
+        return false;
+    }
+    match cond {
+        Condition::All(children) => children.iter().all(|c| {
+            eval_condition(c, depth + 1, tool_name, category, target, trust_score, roles, agg)
+        }),
+        Condition::Any(children) => children.iter().any(|c| {
+            eval_condition(c, depth + 1, tool_name, category, target, trust_score, roles, agg)
+        }),
+        Condition::Not(child) => {
+            !eval_condition(child, depth + 1, tool_name, category, target, trust_score, roles, agg)
+        }
+        Condition::Leaf(spec) => {
+            matches_rule(tool_name, category, target, spec, trust_score, roles, agg)
+        }
+    }
+}
+
+/// Which sub-check of a flat [`PolicyMatch`] rejected a call. Produced by
+/// [`check_rule`] and surfaced through explain mode so authors can see why a
+/// rule did not fire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailedCheck {
+    MinTrust,
+    Roles,
+    Aggregate,
+    Tools,
+    Categories,
+    TargetPatterns,
+    /// A nested [`Condition`] tree evaluated to `false`.
+    Condition,
 }
 
 /// Check if a tool call matches a rule
 #[inline]
+#[allow(clippy::too_many_arguments)]
 fn matches_rule(
     tool_name: &str,
     category: ToolCategory,
     target: Option<&str>,
     match_spec: &PolicyMatch,
     trust_score: f64,
+    roles: &[String],
+    agg: Option<&AggregateCtx>,
 ) -> bool {
+    check_rule(tool_name, category, target, match_spec, trust_score, roles, agg).is_none()
+}
+
+/// Evaluate each predicate of a flat rule in order, returning the first one
+/// that fails (or `None` if they all pass). The bare-`bool` [`matches_rule`] is
+/// a thin wrapper over this, so the match order is identical on both paths.
+#[allow(clippy::too_many_arguments)]
+fn check_rule(
+    tool_name: &str,
+    category: ToolCategory,
+    target: Option<&str>,
+    match_spec: &PolicyMatch,
+    trust_score: f64,
+    roles: &[String],
+    agg: Option<&AggregateCtx>,
+) -> Option<FailedCheck> {
     // Check minimum trust requirement
     if let Some(min_trust) = match_spec.min_trust {
         if trust_score < min_trust {
-            return false;
+            return Some(FailedCheck::MinTrust);
+        }
+    }
+
+    // Check role requirement (any-of against the effective role set)
+    if let Some(required) = &match_spec.roles {
+        if !required.iter().any(|r| roles.iter().any(|e| e == r)) {
+            return Some(FailedCheck::Roles);
+        }
+    }
+
+    // Check the aggregate condition against recent history, if any. Without a
+    // state context (the stateless path) an aggregate rule cannot match.
+    if let Some(cond) = &match_spec.aggregate {
+        match agg {
+            Some(ctx) if cond.satisfied(ctx, tool_name) => {}
+            _ => return Some(FailedCheck::Aggregate),
         }
     }
 
     // Check tool name match
     if let Some(tools) = &match_spec.tools {
         if !tools.iter().any(|t| t == tool_name) {
-            return false;
+            return Some(FailedCheck::Tools);
         }
     }
 
     // Check category match
     if let Some(categories) = &match_spec.categories {
         if !categories.contains(&category) {
-            return false;
+            return Some(FailedCheck::Categories);
         }
     }
 
-    // Check target pattern match
-    if let Some(patterns) = &match_spec.target_patterns {
+    // Check target matchers (any-of semantics across the matcher list),
+    // normalizing the target first when the rule requests it.
+    if let Some(matchers) = &match_spec.target_patterns {
         let Some(target) = target else {
-            return false;
+            return Some(FailedCheck::TargetPatterns);
         };
 
-        let matched = patterns.iter().any(|pattern| {
-            if match_spec.target_patterns_are_regex {
-                // Simple regex matching (avoid full regex crate for WASM size)
-                target.contains(pattern)
-            } else {
-                // Glob matching
-                glob_match(pattern, target)
-            }
-        });
+        let normalized = match &match_spec.normalize {
+            Some(n) => regex_replace(&n.pattern, &n.replacement, target),
+            None => target.to_string(),
+        };
 
-        if !matched {
-            return false;
+        if !matchers.iter().any(|m| m.matches(&normalized)) {
+            return Some(FailedCheck::TargetPatterns);
         }
     }
 
-    true
+    None
 }
 
 /// Simple glob pattern matching
@@ -216,6 +1364,327 @@ fn glob_match(pattern: &str, target: &str) -> bool {
     pattern == target
 }
 
+// ----------------------------------------------------------------------------
+// Tiny backtracking regex matcher
+//
+// A deliberately small engine so the WASM bundle does not pull in the full
+// `regex` crate. It supports `.`, `*`, `+`, `?`, character classes (`[a-z]`,
+// `[^0-9]`), anchors (`^`, `$`), grouping `(...)`, and alternation `|`, with
+// `\` escaping the next character. Matching is unanchored search: the pattern
+// succeeds if it matches at any starting offset.
+// ----------------------------------------------------------------------------
+
+/// A regex atom — the unit a quantifier attaches to.
+enum ReAtom {
+    Start,
+    End,
+    Any,
+    Lit(char),
+    Class { negated: bool, ranges: Vec<(char, char)> },
+    Group(Vec<Vec<ReQuant>>),
+}
+
+/// Quantifier applied to an atom.
+enum ReRep {
+    One,
+    Star,
+    Plus,
+    Opt,
+}
+
+/// A quantified atom within a sequence.
+struct ReQuant {
+    atom: ReAtom,
+    rep: ReRep,
+}
+
+/// Recursive-descent parser for the tiny regex grammar.
+struct ReParser<'a> {
+    chars: &'a [char],
+    pos: usize,
+}
+
+impl<'a> ReParser<'a> {
+    /// alt := seq ('|' seq)*
+    fn parse_alt(&mut self) -> Vec<Vec<ReQuant>> {
+        let mut alts = vec![self.parse_seq()];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            alts.push(self.parse_seq());
+        }
+        alts
+    }
+
+    /// seq := quant*  (stops at '|', ')', or EOF)
+    fn parse_seq(&mut self) -> Vec<ReQuant> {
+        let mut seq = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            if let Some(q) = self.parse_quant() {
+                seq.push(q);
+            } else {
+                break;
+            }
+        }
+        seq
+    }
+
+    fn parse_quant(&mut self) -> Option<ReQuant> {
+        let atom = self.parse_atom()?;
+        let rep = match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                ReRep::Star
+            }
+            Some('+') => {
+                self.pos += 1;
+                ReRep::Plus
+            }
+            Some('?') => {
+                self.pos += 1;
+                ReRep::Opt
+            }
+            _ => ReRep::One,
+        };
+        Some(ReQuant { atom, rep })
+    }
+
+    fn parse_atom(&mut self) -> Option<ReAtom> {
+        let c = self.peek()?;
+        match c {
+            '^' => {
+                self.pos += 1;
+                Some(ReAtom::Start)
+            }
+            '$' => {
+                self.pos += 1;
+                Some(ReAtom::End)
+            }
+            '.' => {
+                self.pos += 1;
+                Some(ReAtom::Any)
+            }
+            '(' => {
+                self.pos += 1;
+                let alt = self.parse_alt();
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                }
+                Some(ReAtom::Group(alt))
+            }
+            '[' => {
+                self.pos += 1;
+                Some(self.parse_class())
+            }
+            '\\' => {
+                self.pos += 1;
+                let lit = self.peek()?;
+                self.pos += 1;
+                Some(ReAtom::Lit(lit))
+            }
+            _ => {
+                self.pos += 1;
+                Some(ReAtom::Lit(c))
+            }
+        }
+    }
+
+    fn parse_class(&mut self) -> ReAtom {
+        let mut negated = false;
+        if self.peek() == Some('^') {
+            negated = true;
+            self.pos += 1;
+        }
+        let mut ranges = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == ']' {
+                self.pos += 1;
+                break;
+            }
+            self.pos += 1;
+            // `a-z` range, unless the `-` is trailing.
+            if self.peek() == Some('-') && self.peek_at(1).is_some_and(|n| n != ']') {
+                self.pos += 1; // consume '-'
+                let end = self.peek().unwrap();
+                self.pos += 1;
+                ranges.push((c, end));
+            } else {
+                ranges.push((c, c));
+            }
+        }
+        ReAtom::Class { negated, ranges }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    #[inline]
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+}
+
+/// A parsed regex, kept so a long-lived [`CompiledPolicy`] can parse each
+/// pattern once and reuse the AST across many evaluations instead of
+/// re-parsing the pattern string on every match.
+pub(crate) struct CompiledRegex {
+    alt: Vec<Vec<ReQuant>>,
+}
+
+impl CompiledRegex {
+    /// Parse `pattern` into its executable form.
+    pub(crate) fn compile(pattern: &str) -> Self {
+        let pat: Vec<char> = pattern.chars().collect();
+        CompiledRegex { alt: ReParser { chars: &pat, pos: 0 }.parse_alt() }
+    }
+
+    /// Whether the pattern matches anywhere in `target` (unanchored search).
+    pub(crate) fn is_match(&self, target: &str) -> bool {
+        let input: Vec<char> = target.chars().collect();
+        (0..=input.len()).any(|start| !match_alt(&self.alt, &input, start).is_empty())
+    }
+
+    /// Replace the first (leftmost, shortest) match with `replacement`,
+    /// returning the target unchanged if the pattern matches nowhere.
+    pub(crate) fn replace_first(&self, replacement: &str, target: &str) -> String {
+        let input: Vec<char> = target.chars().collect();
+        for start in 0..=input.len() {
+            let ends = match_alt(&self.alt, &input, start);
+            if let Some(&end) = ends.iter().min() {
+                let mut out: String = input[..start].iter().collect();
+                out.push_str(replacement);
+                out.extend(&input[end..]);
+                return out;
+            }
+        }
+        target.to_string()
+    }
+}
+
+/// Match `pattern` against `target` as an unanchored search.
+fn regex_match(pattern: &str, target: &str) -> bool {
+    CompiledRegex::compile(pattern).is_match(target)
+}
+
+/// Replace the first (leftmost) match of `pattern` in `target` with
+/// `replacement`, returning the rewritten string; if the pattern matches
+/// nowhere, `target` is returned unchanged.
+///
+/// Used to canonicalize a target before the operator matchers run. Like
+/// [`regex_match`] this is an unanchored search; the shortest match at the
+/// earliest offset is replaced. `replacement` is treated as a literal (no
+/// capture-group substitution), matching the engine's minimal feature set.
+fn regex_replace(pattern: &str, replacement: &str, target: &str) -> String {
+    CompiledRegex::compile(pattern).replace_first(replacement, target)
+}
+
+/// Positions reachable by matching any branch of `alt` from `pos`.
+fn match_alt(alt: &[Vec<ReQuant>], input: &[char], pos: usize) -> Vec<usize> {
+    let mut ends = Vec::new();
+    for seq in alt {
+        ends.extend(match_seq(seq, input, pos));
+    }
+    ends
+}
+
+/// Positions reachable by matching the whole sequence `quants` from `pos`.
+fn match_seq(quants: &[ReQuant], input: &[char], pos: usize) -> Vec<usize> {
+    let Some((first, rest)) = quants.split_first() else {
+        return vec![pos];
+    };
+    let mut ends = Vec::new();
+    for mid in match_rep(first, input, pos) {
+        ends.extend(match_seq(rest, input, mid));
+    }
+    ends
+}
+
+/// Positions reachable by matching a quantified atom from `pos`.
+fn match_rep(quant: &ReQuant, input: &[char], pos: usize) -> Vec<usize> {
+    match quant.rep {
+        ReRep::One => match_atom(&quant.atom, input, pos),
+        ReRep::Opt => {
+            let mut ends = vec![pos];
+            ends.extend(match_atom(&quant.atom, input, pos));
+            ends
+        }
+        ReRep::Star => repeat(&quant.atom, input, pos, true),
+        ReRep::Plus => repeat(&quant.atom, input, pos, false),
+    }
+}
+
+/// Transitive closure of repeatedly matching `atom`. `allow_zero` includes the
+/// starting position (`*` semantics); otherwise at least one match is required
+/// (`+` semantics). A visited set guards against zero-width atoms looping.
+fn repeat(atom: &ReAtom, input: &[char], pos: usize, allow_zero: bool) -> Vec<usize> {
+    let mut ends = Vec::new();
+    if allow_zero {
+        ends.push(pos);
+    }
+    let mut seen = vec![pos];
+    let mut stack = vec![pos];
+    while let Some(p) = stack.pop() {
+        for np in match_atom(atom, input, p) {
+            if !seen.contains(&np) {
+                seen.push(np);
+                stack.push(np);
+                ends.push(np);
+            }
+        }
+    }
+    ends
+}
+
+/// Positions reachable by matching a single atom once from `pos`.
+fn match_atom(atom: &ReAtom, input: &[char], pos: usize) -> Vec<usize> {
+    match atom {
+        ReAtom::Start => {
+            if pos == 0 {
+                vec![pos]
+            } else {
+                vec![]
+            }
+        }
+        ReAtom::End => {
+            if pos == input.len() {
+                vec![pos]
+            } else {
+                vec![]
+            }
+        }
+        ReAtom::Any => {
+            if pos < input.len() {
+                vec![pos + 1]
+            } else {
+                vec![]
+            }
+        }
+        ReAtom::Lit(c) => {
+            if input.get(pos) == Some(c) {
+                vec![pos + 1]
+            } else {
+                vec![]
+            }
+        }
+        ReAtom::Class { negated, ranges } => match input.get(pos) {
+            Some(&c) => {
+                let in_class = ranges.iter().any(|&(lo, hi)| c >= lo && c <= hi);
+                if in_class != *negated {
+                    vec![pos + 1]
+                } else {
+                    vec![]
+                }
+            }
+            None => vec![],
+        },
+        ReAtom::Group(alt) => match_alt(alt, input, pos),
+    }
+}
+
 /// Check rate limit
 pub fn check_rate_limit_impl(
     state_json: &str,
@@ -279,13 +1748,15 @@ mod tests {
             tools: Some(vec!["Read".to_string()]),
             categories: None,
             target_patterns: None,
-            target_patterns_are_regex: false,
             rate_limit: None,
             min_trust: None,
+            roles: None,
+            normalize: None,
+            aggregate: None,
         };
 
-        assert!(matches_rule("Read", ToolCategory::FileRead, None, &match_spec, 0.5));
-        assert!(!matches_rule("Write", ToolCategory::FileWrite, None, &match_spec, 0.5));
+        assert!(matches_rule("Read", ToolCategory::FileRead, None, &match_spec, 0.5, &[], None));
+        assert!(!matches_rule("Write", ToolCategory::FileWrite, None, &match_spec, 0.5, &[], None));
     }
 
     #[test]
@@ -294,12 +1765,402 @@ mod tests {
             tools: None,
             categories: None,
             target_patterns: None,
-            target_patterns_are_regex: false,
             rate_limit: None,
             min_trust: Some(0.7),
+            roles: None,
+            normalize: None,
+            aggregate: None,
         };
 
-        assert!(matches_rule("Read", ToolCategory::FileRead, None, &match_spec, 0.8));
-        assert!(!matches_rule("Read", ToolCategory::FileRead, None, &match_spec, 0.5));
+        assert!(matches_rule("Read", ToolCategory::FileRead, None, &match_spec, 0.8, &[], None));
+        assert!(!matches_rule("Read", ToolCategory::FileRead, None, &match_spec, 0.5, &[], None));
+    }
+
+    #[test]
+    fn test_target_matcher_ops() {
+        let m = |op, value: &str| TargetMatcher { op, value: value.to_string() };
+        assert!(m(MatchOp::Equal, "/etc/passwd").matches("/etc/passwd"));
+        assert!(!m(MatchOp::Equal, "/etc").matches("/etc/passwd"));
+        assert!(m(MatchOp::StartsWith, "/etc/").matches("/etc/passwd"));
+        assert!(m(MatchOp::EndsWith, ".ts").matches("src/main.ts"));
+        assert!(m(MatchOp::Contains, "secret").matches("a/secret/b"));
+        assert!(m(MatchOp::Glob, "src/**").matches("src/a/b.ts"));
+    }
+
+    #[test]
+    fn test_target_matcher_bare_string_is_glob() {
+        let m: TargetMatcher = serde_json::from_str("\"*.ts\"").unwrap();
+        assert_eq!(m.op, MatchOp::Glob);
+        assert!(m.matches("foo.ts"));
+
+        let typed: TargetMatcher =
+            serde_json::from_str(r#"{ "op": "ends_with", "value": ".rs" }"#).unwrap();
+        assert_eq!(typed.op, MatchOp::EndsWith);
+    }
+
+    #[test]
+    fn test_regex_match_features() {
+        // Anchors, classes, and quantifiers.
+        assert!(regex_match("^/etc/[a-z]+$", "/etc/passwd"));
+        assert!(!regex_match("^/etc/[a-z]+$", "/etc/passwd1"));
+        assert!(regex_match("colou?r", "color"));
+        assert!(regex_match("colou?r", "colour"));
+        assert!(regex_match("a.*z", "abcz"));
+        // Alternation and grouping.
+        assert!(regex_match("(foo|bar)baz", "barbaz"));
+        assert!(!regex_match("(foo|bar)baz", "quxbaz"));
+        // Negated class.
+        assert!(regex_match("[^0-9]+", "abc"));
+        // Unanchored search finds a match mid-string.
+        assert!(regex_match("b+", "aaabbbccc"));
+    }
+
+    #[test]
+    fn test_condition_tree_all_any_not() {
+        let leaf_write = Condition::Leaf(PolicyMatch {
+            tools: Some(vec!["Write".to_string()]),
+            categories: None,
+            target_patterns: None,
+            rate_limit: None,
+            min_trust: None,
+            roles: None,
+            normalize: None,
+            aggregate: None,
+        });
+        let leaf_low_trust = Condition::Not(Box::new(Condition::Leaf(PolicyMatch {
+            tools: None,
+            categories: None,
+            target_patterns: None,
+            rate_limit: None,
+            min_trust: Some(0.3),
+            roles: None,
+            normalize: None,
+            aggregate: None,
+        })));
+        // Write AND trust < 0.3
+        let tree = Condition::All(vec![leaf_write, leaf_low_trust]);
+
+        assert!(eval_condition(&tree, 0, "Write", ToolCategory::FileWrite, None, 0.1, &[], None));
+        assert!(!eval_condition(&tree, 0, "Write", ToolCategory::FileWrite, None, 0.9, &[], None));
+        assert!(!eval_condition(&tree, 0, "Read", ToolCategory::FileRead, None, 0.1, &[], None));
+    }
+
+    #[test]
+    fn test_condition_empty_all_any() {
+        let all = Condition::All(vec![]);
+        let any = Condition::Any(vec![]);
+        assert!(eval_condition(&all, 0, "Read", ToolCategory::FileRead, None, 0.5, &[], None));
+        assert!(!eval_condition(&any, 0, "Read", ToolCategory::FileRead, None, 0.5, &[], None));
+    }
+
+    #[test]
+    fn test_flat_match_still_deserializes() {
+        let rule_json = r#"{ "id": "r", "name": "n", "priority": 0,
+            "match": { "tools": ["Read"] }, "decision": "allow" }"#;
+        let rule: PolicyRule = serde_json::from_str(rule_json).unwrap();
+        assert!(matches!(rule.match_spec, RuleMatch::Flat(_)));
+        assert!(rule.match_spec.matches("Read", ToolCategory::FileRead, None, 0.5, &[], None));
+    }
+
+    #[test]
+    fn test_effective_roles_transitive_and_cycle_safe() {
+        let mut grants: FxHashMap<String, Vec<String>> = FxHashMap::default();
+        grants.insert("admin".to_string(), vec!["writer".to_string()]);
+        grants.insert("writer".to_string(), vec!["reader".to_string()]);
+        // A cycle that must not loop forever.
+        grants.insert("reader".to_string(), vec!["admin".to_string()]);
+
+        let effective = effective_roles(&["admin".to_string()], &grants);
+        assert!(effective.contains(&"admin".to_string()));
+        assert!(effective.contains(&"writer".to_string()));
+        assert!(effective.contains(&"reader".to_string()));
+        assert_eq!(effective.len(), 3);
+    }
+
+    #[test]
+    fn test_roles_constraint_via_inheritance() {
+        let policy_json = r#"{
+            "name": "p",
+            "version": "1",
+            "enforce": true,
+            "default_policy": "deny",
+            "role_grants": { "admin": ["writer"] },
+            "rules": [
+                { "id": "r1", "name": "writers may write", "priority": 0,
+                  "match": { "roles": ["writer"] }, "decision": "allow" }
+            ]
+        }"#;
+        let policy: PolicyConfig = serde_json::from_str(policy_json).unwrap();
+        let entity = EntityTrust {
+            entity_id: "e".to_string(),
+            t3: T3Tensor::default(),
+            v3: None,
+            interaction_count: 0,
+            roles: vec!["admin".to_string()],
+        };
+
+        let eval = evaluate(&policy, "Write", None, &entity);
+        assert_eq!(eval.decision, PolicyDecision::Allow);
+        assert!(eval.constraints.iter().any(|c| c.starts_with("effective_roles:")));
+    }
+
+    #[test]
+    fn test_policy_engine_reuse() {
+        let policy_json = r#"{
+            "name": "p",
+            "version": "1",
+            "enforce": true,
+            "default_policy": "deny",
+            "rules": [
+                { "id": "r1", "name": "allow read", "priority": 0,
+                  "match": { "tools": ["Read"] }, "decision": "allow" }
+            ]
+        }"#;
+        let mut engine = PolicyEngine::new(policy_json).unwrap();
+        let entity = EntityTrust {
+            entity_id: "e".to_string(),
+            t3: T3Tensor::default(),
+            v3: None,
+            interaction_count: 0,
+            roles: Vec::new(),
+        };
+
+        let allow = engine.evaluate("Read", None, &entity);
+        assert_eq!(allow.decision, PolicyDecision::Allow);
+        assert_eq!(allow.matched_rule.map(|s| s.as_str().to_string()), Some("r1".to_string()));
+
+        // Same engine, a second call that falls through to the default.
+        let deny = engine.evaluate("Bash", None, &entity);
+        assert_eq!(deny.decision, PolicyDecision::Deny);
+        assert!(deny.matched_rule.is_none());
+
+        engine.reset();
+    }
+
+    #[test]
+    fn test_regex_replace_normalizes_target() {
+        // Collapse any per-user home prefix to a fixed sentinel, then match it.
+        assert_eq!(
+            regex_replace("/home/[a-z]+/", "~/", "/home/alice/secrets"),
+            "~/secrets"
+        );
+        // No match leaves the target untouched.
+        assert_eq!(regex_replace("^/etc/", "", "/var/log"), "/var/log");
+    }
+
+    #[test]
+    fn test_normalize_applied_before_matchers() {
+        let match_spec = PolicyMatch {
+            tools: None,
+            categories: None,
+            target_patterns: Some(vec![TargetMatcher {
+                op: MatchOp::Equal,
+                value: "~/secrets".to_string(),
+            }]),
+            rate_limit: None,
+            min_trust: None,
+            roles: None,
+            normalize: Some(RegexReplace {
+                pattern: "/home/[a-z]+/".to_string(),
+                replacement: "~/".to_string(),
+            }),
+            aggregate: None,
+        };
+        assert!(matches_rule(
+            "Read",
+            ToolCategory::FileRead,
+            Some("/home/bob/secrets"),
+            &match_spec,
+            0.5,
+            &[],
+            None,
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_condition_counts_window() {
+        let mut state = RateLimiterState { windows: FxHashMap::default() };
+        state
+            .windows
+            .insert("e:Bash".to_string(), vec![100, 150, 200]);
+        let ctx = AggregateCtx { now: 250, state: &state, entity_id: "e" };
+        let cond = AggregateCondition {
+            metric: AggregateMetric::RecentCalls,
+            op: CompareOp::Gte,
+            window_ms: 200,
+            threshold: 3,
+        };
+        // All three timestamps fall inside the 200ms window.
+        assert!(cond.satisfied(&ctx, "Bash"));
+
+        // A 60ms window drops the two oldest entries, leaving one — below the threshold.
+        let narrow = AggregateCondition { window_ms: 60, ..cond };
+        assert!(!narrow.satisfied(&ctx, "Bash"));
+
+        // Without a state context the aggregate rule fails closed.
+        let match_spec = PolicyMatch {
+            tools: None,
+            categories: None,
+            target_patterns: None,
+            rate_limit: None,
+            min_trust: None,
+            roles: None,
+            normalize: None,
+            aggregate: Some(cond),
+        };
+        assert!(!matches_rule("Bash", ToolCategory::Execute, None, &match_spec, 0.5, &[], None));
+    }
+
+    #[test]
+    fn test_record_call_prunes_expired_timestamps() {
+        let mut state = RateLimiterState { windows: FxHashMap::default() };
+        // Seed a window with entries, most of them older than the 200ms horizon.
+        state
+            .windows
+            .insert("e:Bash".to_string(), vec![10, 500, 900]);
+
+        // now = 1000, retention 200ms => horizon 800; only 900 survives, plus now.
+        record_call(&mut state, "e", "Bash", 1000, PolicyDecision::Allow, 200);
+        assert_eq!(state.windows["e:Bash"], vec![900, 1000]);
+
+        // A long-lived host hammering the same tool does not grow the window
+        // beyond the retention horizon.
+        for t in 1001..=2000 {
+            record_call(&mut state, "e", "Bash", t, PolicyDecision::Allow, 200);
+        }
+        let window = &state.windows["e:Bash"];
+        assert!(window.len() <= 201, "window grew unbounded: {}", window.len());
+        assert_eq!(*window.last().unwrap(), 2000);
+    }
+
+    #[test]
+    fn test_batch_report_rollups() {
+        let policy_json = r#"{
+            "name": "p",
+            "version": "1",
+            "enforce": true,
+            "default_policy": "ask_user",
+            "rules": [
+                { "id": "allow-read", "name": "allow reads", "priority": 0,
+                  "match": { "tools": ["Read"] }, "decision": "allow" },
+                { "id": "deny-bash", "name": "deny bash", "priority": 0,
+                  "match": { "tools": ["Bash"] }, "decision": "deny" }
+            ]
+        }"#;
+        let calls_json = r#"[
+            { "tool_name": "Read" },
+            { "tool_name": "Read" },
+            { "tool_name": "Bash" },
+            { "tool_name": "Unknown" }
+        ]"#;
+        let entity_json = r#"{ "entity_id": "e", "t3": { "talent": 0.5, "training": 0.5, "temperament": 0.5 } }"#;
+
+        let out = evaluate_policy_batch_impl(policy_json, calls_json, entity_json).unwrap();
+        let report: BatchReport = serde_json::from_str(&out).unwrap();
+
+        assert_eq!(report.calls.len(), 4);
+        assert_eq!(report.total_allowed, 2);
+        assert_eq!(report.total_denied, 1);
+        assert_eq!(report.total_warn, 1);
+        assert_eq!(report.rule_fire_counts.get("allow-read"), Some(&2));
+        assert_eq!(report.rule_fire_counts.get("deny-bash"), Some(&1));
+        // Only the unknown tool fell through to the default policy.
+        assert_eq!(report.default_policy_calls, vec![3]);
+    }
+
+    #[test]
+    fn test_explain_reports_failing_check() {
+        let policy_json = r#"{
+            "name": "p",
+            "version": "1",
+            "enforce": true,
+            "default_policy": "deny",
+            "rules": [
+                { "id": "need-trust", "name": "high trust", "priority": 0,
+                  "match": { "tools": ["Read"], "min_trust": 0.9 }, "decision": "allow" },
+                { "id": "allow-read", "name": "allow reads", "priority": 1,
+                  "match": { "tools": ["Read"] }, "decision": "allow" }
+            ]
+        }"#;
+        let entity_json = r#"{ "entity_id": "e", "t3": { "talent": 0.5, "training": 0.5, "temperament": 0.5 } }"#;
+
+        let out = explain_policy_impl(policy_json, "Read", None, entity_json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+        let trace: Vec<RuleTrace> = serde_json::from_value(parsed["trace"].clone()).unwrap();
+
+        // The first rule is examined and rejected on min_trust; the second
+        // matches and stops the scan.
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[0].rule_id, "need-trust");
+        assert!(!trace[0].matched);
+        assert_eq!(trace[0].failed_check, Some(FailedCheck::MinTrust));
+        assert_eq!(trace[1].rule_id, "allow-read");
+        assert!(trace[1].matched);
+        assert_eq!(parsed["evaluation"]["matched_rule"], "allow-read");
+    }
+
+    #[test]
+    fn test_compiled_policy_indexes_and_matches() {
+        let policy_json = r#"{
+            "name": "p",
+            "version": "1",
+            "enforce": true,
+            "default_policy": "deny",
+            "rules": [
+                { "id": "allow-read", "name": "allow reads", "priority": 0,
+                  "match": { "tools": ["Read"] }, "decision": "allow" },
+                { "id": "deny-exec", "name": "deny exec", "priority": 1,
+                  "match": { "categories": ["execute"] }, "decision": "deny" },
+                { "id": "regex-secret", "name": "block secrets", "priority": 2,
+                  "match": { "target_patterns": [{ "op": "regex", "value": "secret" }] },
+                  "decision": "deny" }
+            ]
+        }"#;
+        let compiled = CompiledPolicy::compile(policy_json).unwrap();
+        // The tool-specific rule is indexed by name, the category rule by
+        // category, and the unconstrained target rule lands in the any bucket.
+        assert_eq!(compiled.by_tool.get("Read"), Some(&vec![0]));
+        assert_eq!(compiled.by_category.get(&ToolCategory::Execute), Some(&vec![1]));
+        assert_eq!(compiled.any_tool, vec![2]);
+
+        let entity = EntityTrust {
+            entity_id: "e".to_string(),
+            t3: T3Tensor::default(),
+            v3: None,
+            interaction_count: 0,
+            roles: Vec::new(),
+        };
+        assert_eq!(compiled.evaluate("Read", None, &entity, None).decision, PolicyDecision::Allow);
+        assert_eq!(compiled.evaluate("Bash", None, &entity, None).decision, PolicyDecision::Deny);
+        // The any-bucket target rule still fires for an otherwise-unmatched tool.
+        assert_eq!(
+            compiled.evaluate("Write", Some("my-secret.txt"), &entity, None).matched_rule,
+            Some("regex-secret".to_string())
+        );
+    }
+
+    #[test]
+    fn test_compile_evaluate_free_handle() {
+        let policy_json = r#"{
+            "name": "p",
+            "version": "1",
+            "enforce": true,
+            "default_policy": "deny",
+            "rules": [
+                { "id": "allow-read", "name": "allow reads", "priority": 0,
+                  "match": { "tools": ["Read"] }, "decision": "allow" }
+            ]
+        }"#;
+        let entity_json = r#"{ "entity_id": "e", "t3": { "talent": 0.5, "training": 0.5, "temperament": 0.5 } }"#;
+
+        let handle = compile_policy_impl(policy_json).unwrap();
+        let out = evaluate_compiled_impl(handle, "Read", None, entity_json).unwrap();
+        let eval: PolicyEvaluation = serde_json::from_str(&out).unwrap();
+        assert_eq!(eval.decision, PolicyDecision::Allow);
+
+        assert!(free_policy_impl(handle));
+        // A second free is a no-op, and the handle no longer evaluates.
+        assert!(!free_policy_impl(handle));
+        assert!(evaluate_compiled_impl(handle, "Read", None, entity_json).is_err());
     }
 }
@@ -0,0 +1,207 @@
+//! Deterministic replay for the audit chain
+//!
+//! A trace is a sequence of `(policy, tool_call, outcome)` operations. This
+//! module decodes a trace and re-executes it against a fresh audit chain,
+//! verifying that every entry hash links to its predecessor (R6 chain
+//! integrity), so a recorded decision sequence can be reproduced and audited.
+//! Traces are not recorded here or by the fuzz targets; the `replay_audit`
+//! fuzz target feeds arbitrary bytes to [`replay_audit_impl`], and its crash
+//! inputs can be kept as regression fixtures.
+//!
+//! Traces use the compact [`crate::codec`] layout.
+
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::PolicyDecision;
+use crate::codec::{Encode, Decode, CodecError};
+use crate::policy::{EntityTrust, PolicyConfig};
+use crate::audit::{AuditChain, R6ActionInput, append_action};
+
+/// One recorded tool call and its observed outcome.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub tool_name: String,
+    pub target: Option<String>,
+    pub success: bool,
+}
+
+/// A replayable sequence of operations against one policy and entity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trace {
+    pub session_id: String,
+    pub policy: PolicyConfig,
+    pub entity: EntityTrust,
+    pub steps: Vec<TraceStep>,
+}
+
+impl Encode for TraceStep {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.tool_name.encode_to(out);
+        self.target.encode_to(out);
+        self.success.encode_to(out);
+    }
+}
+impl Decode for TraceStep {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(TraceStep {
+            tool_name: String::decode(input)?,
+            target: Option::decode(input)?,
+            success: bool::decode(input)?,
+        })
+    }
+}
+
+impl Encode for Trace {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        self.session_id.encode_to(out);
+        self.policy.encode_to(out);
+        self.entity.encode_to(out);
+        self.steps.encode_to(out);
+    }
+}
+impl Decode for Trace {
+    fn decode(input: &mut &[u8]) -> Result<Self, CodecError> {
+        Ok(Trace {
+            session_id: String::decode(input)?,
+            policy: PolicyConfig::decode(input)?,
+            entity: EntityTrust::decode(input)?,
+            steps: Vec::decode(input)?,
+        })
+    }
+}
+
+/// Re-execute a recorded trace and verify the resulting audit chain.
+///
+/// Returns the rebuilt chain, the ordered actions, and a `verified` flag that
+/// is true only if every action's `previous_hash` links to the prior entry.
+pub fn replay_audit_impl(trace: &[u8]) -> Result<String, JsValue> {
+    let mut cursor = trace;
+    let trace = Trace::decode(&mut cursor)
+        .map_err(|e| JsValue::from_str(&format!("Invalid trace blob: {}", e.0)))?;
+
+    let mut chain = AuditChain::new(trace.session_id.clone(), trace.policy.name.clone());
+    let mut actions = Vec::with_capacity(trace.steps.len());
+    let mut verified = true;
+    let mut previous_hash: Option<String> = None;
+
+    for step in &trace.steps {
+        let eval = crate::policy::evaluate(
+            &trace.policy,
+            &step.tool_name,
+            step.target.as_deref(),
+            &trace.entity,
+        );
+
+        let input = R6ActionInput {
+            policy_id: trace.policy.name.clone(),
+            policy_hash: String::new(),
+            matched_rule: eval.matched_rule.clone(),
+            decision: eval.decision,
+            session_id: trace.session_id.clone(),
+            agent_id: None,
+            trust_score: eval.trust_score,
+            tool_name: step.tool_name.clone(),
+            parameters_hash: String::new(),
+            target: step.target.clone(),
+            success: step.success,
+            enforced: eval.enforced,
+            blocked: eval.decision == PolicyDecision::Deny,
+            error: None,
+        };
+
+        let action = append_action(&mut chain, input);
+
+        // Chain integrity: this entry must link to the previous content hash.
+        if action.reference.previous_hash != previous_hash {
+            verified = false;
+        }
+        previous_hash = Some(action.content_hash.clone());
+        actions.push(action);
+    }
+
+    let response = serde_json::json!({
+        "verified": verified,
+        "chain": chain,
+        "actions": actions,
+    });
+
+    serde_json::to_string(&response)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::T3Tensor;
+    use crate::policy::{PolicyRule, PolicyMatch, RuleMatch};
+
+    fn sample_trace() -> Trace {
+        Trace {
+            session_id: "session:replay".to_string(),
+            policy: PolicyConfig {
+                name: "p".to_string(),
+                version: "1".to_string(),
+                enforce: true,
+                default_policy: PolicyDecision::Allow,
+                rules: vec![PolicyRule {
+                    id: "deny-bash".to_string(),
+                    name: "Deny bash".to_string(),
+                    priority: 0,
+                    match_spec: RuleMatch::Flat(PolicyMatch {
+                        tools: Some(vec!["Bash".to_string()]),
+                        categories: None,
+                        target_patterns: None,
+                        rate_limit: None,
+                        min_trust: None,
+                        roles: None,
+                        normalize: None,
+                        aggregate: None,
+                    }),
+                    decision: PolicyDecision::Deny,
+                    reason: None,
+                }],
+                role_grants: gastown_shared::FxHashMap::default(),
+            },
+            entity: EntityTrust {
+                entity_id: "e".to_string(),
+                t3: T3Tensor::default(),
+                v3: None,
+                interaction_count: 0,
+                roles: Vec::new(),
+            },
+            steps: vec![
+                TraceStep { tool_name: "Read".to_string(), target: None, success: true },
+                TraceStep { tool_name: "Bash".to_string(), target: None, success: false },
+                TraceStep { tool_name: "Write".to_string(), target: None, success: true },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_replay_links_and_verifies() {
+        let bytes = sample_trace().encode();
+        let out = replay_audit_impl(&bytes).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+        assert_eq!(parsed["verified"], true);
+        assert_eq!(parsed["actions"].as_array().unwrap().len(), 3);
+        // The Bash call is denied and therefore blocked.
+        assert_eq!(parsed["actions"][1]["result"]["blocked"], true);
+    }
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        let bytes = sample_trace().encode();
+        let a = replay_audit_impl(&bytes).unwrap();
+        let b = replay_audit_impl(&bytes).unwrap();
+        // Entry hashes depend only on the recorded fields, so they must match.
+        let va: serde_json::Value = serde_json::from_str(&a).unwrap();
+        let vb: serde_json::Value = serde_json::from_str(&b).unwrap();
+        assert_eq!(va["actions"][0]["content_hash"], vb["actions"][0]["content_hash"]);
+    }
+
+    #[test]
+    fn test_garbage_trace_errors_not_panics() {
+        assert!(replay_audit_impl(&[0xff, 0x00, 0x13]).is_err());
+    }
+}
@@ -2,7 +2,7 @@
 
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use crate::{T3Tensor, TrustLevel, ToolCategory};
+use crate::{T3Tensor, V3Tensor, TrustLevel, ToolCategory};
 
 /// Entity trust record
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +10,10 @@ pub struct EntityTrustRecord {
     pub entity_id: String,
     pub entity_type: EntityType,
     pub t3: T3Tensor,
+    /// Accrued value tensor, evolved alongside `t3` on each outcome. Optional so
+    /// records that predate V3 tracking still deserialize.
+    #[serde(default)]
+    pub v3: Option<V3Tensor>,
     pub level: TrustLevel,
     pub interaction_count: u64,
     pub success_count: u64,
@@ -36,6 +40,7 @@ impl EntityTrustRecord {
             entity_id,
             entity_type,
             t3: T3Tensor::default(),
+            v3: Some(V3Tensor::default()),
             level: TrustLevel::Medium,
             interaction_count: 0,
             success_count: 0,
@@ -55,6 +60,13 @@ impl EntityTrustRecord {
             self.failure_count += 1;
         }
         self.t3.update_from_outcome(success, is_novel);
+        // Evolve accrued value alongside capability trust: a success on an
+        // established (non-novel) action counts as validated, so veracity
+        // climbs; a failure is a contested outcome that decays validity. A
+        // legacy record without a value tensor starts from the default here.
+        self.v3
+            .get_or_insert_with(V3Tensor::default)
+            .update_from_outcome(success, success && !is_novel);
         self.level = self.t3.level();
         self.last_updated = js_sys::Date::new_0().to_iso_string().as_string().unwrap_or_default();
     }
@@ -139,6 +151,16 @@ mod tests {
         assert_eq!(entity.interaction_count, 1);
         assert_eq!(entity.success_count, 1);
         assert!(entity.t3.composite() > 0.5);
+        // A validated success lifts veracity above its 0.5 default.
+        assert!(entity.v3.unwrap().veracity > 0.5);
+    }
+
+    #[test]
+    fn test_update_evolves_v3() {
+        let mut entity = EntityTrustRecord::new("mcp:test".to_string(), EntityType::Tool);
+        // A contested (failed) outcome decays validity below its default.
+        entity.update_from_outcome(false, false);
+        assert!(entity.v3.unwrap().validity < 0.5);
     }
 
     #[test]
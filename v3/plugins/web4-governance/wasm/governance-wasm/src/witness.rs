@@ -3,6 +3,7 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 use crate::TrustLevel;
+use gastown_shared::FxHashMap;
 
 /// Witness event record
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +95,264 @@ impl WitnessingChain {
     }
 }
 
+/// Converged global trust score for one entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalTrustScore {
+    pub entity_id: String,
+    pub score: f64,
+    pub trust_level: TrustLevel,
+}
+
+/// Result of the graph-wide EigenTrust computation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalTrustResult {
+    pub scores: Vec<GlobalTrustScore>,
+    pub iterations: u32,
+    pub converged: bool,
+}
+
+/// Dampening factor guarding against malicious collectives / disconnected components
+const EIGENTRUST_ALPHA: f64 = 0.15;
+/// L1 convergence threshold
+const EIGENTRUST_EPSILON: f64 = 1e-6;
+/// Maximum power-iteration steps
+const EIGENTRUST_MAX_ITERS: u32 = 100;
+
+/// Compute a globally consistent trust vector over the witnessing graph.
+///
+/// Implements the EigenTrust power iteration: from the aggregated local trust
+/// matrix `s_ij` (the trust witness `i` places in `j`) we build the normalized
+/// matrix `c_ij = max(s_ij, 0) / Σ_j max(s_ij, 0)`, falling back to the
+/// pre-trusted distribution `p` for rows that sum to zero, then iterate
+/// `t^(k+1) = (1-a)·Cᵀ·t^(k) + a·p` from `t^(0) = p` until the L1 delta drops
+/// below [`EIGENTRUST_EPSILON`] or the iteration cap is hit.
+///
+/// `pretrusted_json` is an optional `{ entity_id: weight }` map; absent or empty,
+/// a uniform distribution over all observed entities is used.
+pub fn compute_global_trust_impl(
+    events_json: &str,
+    pretrusted_json: &str,
+) -> Result<String, JsValue> {
+    let events: Vec<WitnessEvent> = serde_json::from_str(events_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid events JSON: {}", e)))?;
+
+    let pretrusted: FxHashMap<String, f64> =
+        serde_json::from_str(pretrusted_json).unwrap_or_default();
+
+    // Index every entity that appears as a witness or is witnessed.
+    let mut ids: Vec<String> = Vec::new();
+    let mut index: FxHashMap<String, usize> = FxHashMap::default();
+    let mut intern = |id: &str, ids: &mut Vec<String>, index: &mut FxHashMap<String, usize>| -> usize {
+        if let Some(&i) = index.get(id) {
+            i
+        } else {
+            let i = ids.len();
+            ids.push(id.to_string());
+            index.insert(id.to_string(), i);
+            i
+        }
+    };
+    for ev in &events {
+        intern(&ev.witness_id, &mut ids, &mut index);
+        intern(&ev.witnessed_id, &mut ids, &mut index);
+    }
+
+    let n = ids.len();
+    if n == 0 {
+        let result = GlobalTrustResult { scores: Vec::new(), iterations: 0, converged: true };
+        return serde_json::to_string(&result)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)));
+    }
+
+    // Aggregate local trust s_ij (sum of attestation scores i -> j).
+    let mut s = vec![vec![0.0f64; n]; n];
+    for ev in &events {
+        let i = index[&ev.witness_id];
+        let j = index[&ev.witnessed_id];
+        s[i][j] += ev.trust_score.max(0.0);
+    }
+
+    // Pre-trusted distribution p (uniform unless supplied), normalized to sum 1.
+    let mut p = vec![0.0f64; n];
+    let pre_total: f64 = ids.iter().filter_map(|id| pretrusted.get(id)).map(|w| w.max(0.0)).sum();
+    if pre_total > 0.0 {
+        for (i, id) in ids.iter().enumerate() {
+            p[i] = pretrusted.get(id).copied().unwrap_or(0.0).max(0.0) / pre_total;
+        }
+    } else {
+        let uniform = 1.0 / n as f64;
+        p.iter_mut().for_each(|v| *v = uniform);
+    }
+
+    // Row-normalize into C, falling back to p for zero-sum rows.
+    let mut c = vec![vec![0.0f64; n]; n];
+    for i in 0..n {
+        let row_sum: f64 = s[i].iter().sum();
+        if row_sum > 0.0 {
+            for j in 0..n {
+                c[i][j] = s[i][j] / row_sum;
+            }
+        } else {
+            c[i].clone_from(&p);
+        }
+    }
+
+    // Power iteration: t' = (1-a)·Cᵀ·t + a·p
+    let mut t = p.clone();
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < EIGENTRUST_MAX_ITERS {
+        let mut next = vec![0.0f64; n];
+        for j in 0..n {
+            let mut acc = 0.0;
+            for i in 0..n {
+                acc += c[i][j] * t[i];
+            }
+            next[j] = (1.0 - EIGENTRUST_ALPHA) * acc + EIGENTRUST_ALPHA * p[j];
+        }
+
+        let delta: f64 = next.iter().zip(t.iter()).map(|(a, b)| (a - b).abs()).sum();
+        t = next;
+        iterations += 1;
+        if delta < EIGENTRUST_EPSILON {
+            converged = true;
+            break;
+        }
+    }
+
+    let scores = ids
+        .into_iter()
+        .enumerate()
+        .map(|(i, entity_id)| GlobalTrustScore {
+            entity_id,
+            score: t[i],
+            trust_level: TrustLevel::from_score(t[i]),
+        })
+        .collect();
+
+    let result = GlobalTrustResult { scores, iterations, converged };
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// A single attestation submitted about a witnessed entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attestation {
+    /// The attesting witness
+    pub witness_id: String,
+    /// Score the witness assigns to the target, in [0, 1]
+    pub score: f64,
+    /// The witness's own T3 composite, used as the aggregation weight
+    pub witness_composite: f64,
+}
+
+/// Result of Byzantine-fault-tolerant consensus over a set of attestations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusResult {
+    pub consensus_score: f64,
+    pub trust_level: TrustLevel,
+    /// Number of attestations that fed the final trust-weighted mean
+    pub witnesses_used: usize,
+    /// Witnesses dropped as the outlying top/bottom `f` of the sorted scores
+    pub excluded: Vec<String>,
+    /// Witnesses excluded entirely for submitting divergent scores
+    pub flagged_equivocators: Vec<String>,
+    /// Whether the quorum requirement `N >= 3f + 1` was met
+    pub quorum_met: bool,
+}
+
+/// Aggregate independent attestations into one robust trust value.
+///
+/// Tolerates up to `f` malicious witnesses, requiring `N >= 3f + 1` honest
+/// attestations. Witnesses that equivocate (submit two scores for the target
+/// differing by more than `epsilon`) are flagged and excluded entirely; of the
+/// remainder, each witness's scores are collapsed to their mean, weighted by
+/// the witness's `T3Tensor::composite()`. The weighted scores are sorted, the
+/// top `f` and bottom `f` discarded as potential adversaries, and the
+/// trust-weighted mean of what remains is returned as the consensus score.
+pub fn aggregate_consensus_impl(
+    attestations_json: &str,
+    f: usize,
+    epsilon: f64,
+) -> Result<String, JsValue> {
+    let attestations: Vec<Attestation> = serde_json::from_str(attestations_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid attestations JSON: {}", e)))?;
+
+    // Group attestations by witness to detect equivocation and collapse dupes.
+    let mut by_witness: FxHashMap<String, Vec<&Attestation>> = FxHashMap::default();
+    for att in &attestations {
+        by_witness.entry(att.witness_id.clone()).or_default().push(att);
+    }
+
+    let mut flagged_equivocators = Vec::new();
+    // One representative (witness_id, mean_score, weight) per honest witness.
+    let mut honest: Vec<(String, f64, f64)> = Vec::new();
+    for (witness_id, atts) in by_witness {
+        let min = atts.iter().map(|a| a.score).fold(f64::INFINITY, f64::min);
+        let max = atts.iter().map(|a| a.score).fold(f64::NEG_INFINITY, f64::max);
+        if max - min > epsilon {
+            flagged_equivocators.push(witness_id);
+            continue;
+        }
+        let mean_score = atts.iter().map(|a| a.score).sum::<f64>() / atts.len() as f64;
+        let weight = atts.iter().map(|a| a.witness_composite).sum::<f64>() / atts.len() as f64;
+        honest.push((witness_id, mean_score, weight));
+    }
+
+    flagged_equivocators.sort();
+
+    let n = honest.len();
+    let quorum_met = n >= 3 * f + 1;
+
+    if !quorum_met {
+        let result = ConsensusResult {
+            consensus_score: 0.0,
+            trust_level: TrustLevel::Unknown,
+            witnesses_used: 0,
+            excluded: Vec::new(),
+            flagged_equivocators,
+            quorum_met: false,
+        };
+        return serde_json::to_string(&result)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)));
+    }
+
+    // Sort by the weighted score and trim the extreme `f` from each end as
+    // potential adversaries.
+    honest.sort_by(|a, b| {
+        (a.1 * a.2)
+            .partial_cmp(&(b.1 * b.2))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let excluded: Vec<String> = honest
+        .iter()
+        .take(f)
+        .chain(honest.iter().rev().take(f))
+        .map(|(id, _, _)| id.clone())
+        .collect();
+
+    let kept = &honest[f..n - f];
+    let weight_total: f64 = kept.iter().map(|(_, _, w)| *w).sum();
+    let consensus_score = if weight_total > 0.0 {
+        kept.iter().map(|(_, s, w)| s * w).sum::<f64>() / weight_total
+    } else {
+        // Degenerate weights: fall back to the unweighted mean.
+        kept.iter().map(|(_, s, _)| s).sum::<f64>() / kept.len() as f64
+    };
+
+    let result = ConsensusResult {
+        consensus_score,
+        trust_level: TrustLevel::from_score(consensus_score),
+        witnesses_used: kept.len(),
+        excluded,
+        flagged_equivocators,
+        quorum_met: true,
+    };
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
 /// Record a witness event
 pub fn record_witness_impl(
     witness_id: &str,
@@ -146,4 +405,92 @@ mod tests {
         // 0.5 * 0.7 + 0.9 * 0.3 = 0.35 + 0.27 = 0.62
         assert!((transitive - 0.62).abs() < 0.01);
     }
+
+    #[test]
+    fn test_global_trust_converges_and_normalizes() {
+        // a -> b, b -> c, c -> a (trust cycle)
+        let events = vec![
+            WitnessEvent::new("a".to_string(), "b".to_string(), 1.0),
+            WitnessEvent::new("b".to_string(), "c".to_string(), 1.0),
+            WitnessEvent::new("c".to_string(), "a".to_string(), 1.0),
+        ];
+        let events_json = serde_json::to_string(&events).unwrap();
+
+        let out = compute_global_trust_impl(&events_json, "{}").unwrap();
+        let result: GlobalTrustResult = serde_json::from_str(&out).unwrap();
+
+        assert!(result.converged);
+        assert_eq!(result.scores.len(), 3);
+        // Trust vector is a probability distribution summing to ~1.
+        let total: f64 = result.scores.iter().map(|s| s.score).sum();
+        assert!((total - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_global_trust_pretrusted_bias() {
+        // a witnesses b; a is the only pre-trusted node.
+        let events = vec![WitnessEvent::new("a".to_string(), "b".to_string(), 1.0)];
+        let events_json = serde_json::to_string(&events).unwrap();
+
+        let out = compute_global_trust_impl(&events_json, r#"{"a":1.0}"#).unwrap();
+        let result: GlobalTrustResult = serde_json::from_str(&out).unwrap();
+        // a is the sole pre-trusted source, so it anchors a positive score.
+        let a = result.scores.iter().find(|s| s.entity_id == "a").unwrap();
+        assert!(a.score > 0.0);
+    }
+
+    #[test]
+    fn test_consensus_trims_outliers() {
+        // N = 7, f = 1 -> quorum met. One low and one high outlier get trimmed.
+        let atts: Vec<Attestation> = [
+            ("w1", 0.1), ("w2", 0.7), ("w3", 0.72), ("w4", 0.71),
+            ("w5", 0.69), ("w6", 0.73), ("w7", 0.99),
+        ]
+        .iter()
+        .map(|(id, score)| Attestation {
+            witness_id: id.to_string(),
+            score: *score,
+            witness_composite: 0.8,
+        })
+        .collect();
+        let json = serde_json::to_string(&atts).unwrap();
+
+        let out = aggregate_consensus_impl(&json, 1, 0.05).unwrap();
+        let result: ConsensusResult = serde_json::from_str(&out).unwrap();
+
+        assert!(result.quorum_met);
+        assert_eq!(result.witnesses_used, 5);
+        assert!(result.excluded.contains(&"w1".to_string()));
+        assert!(result.excluded.contains(&"w7".to_string()));
+        // Consensus sits around the honest cluster near 0.7.
+        assert!((result.consensus_score - 0.71).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_consensus_flags_equivocation() {
+        let atts = vec![
+            Attestation { witness_id: "w1".to_string(), score: 0.2, witness_composite: 0.8 },
+            Attestation { witness_id: "w1".to_string(), score: 0.9, witness_composite: 0.8 },
+            Attestation { witness_id: "w2".to_string(), score: 0.7, witness_composite: 0.8 },
+        ];
+        let json = serde_json::to_string(&atts).unwrap();
+
+        let out = aggregate_consensus_impl(&json, 0, 0.05).unwrap();
+        let result: ConsensusResult = serde_json::from_str(&out).unwrap();
+        assert_eq!(result.flagged_equivocators, vec!["w1".to_string()]);
+    }
+
+    #[test]
+    fn test_consensus_requires_quorum() {
+        let atts = vec![
+            Attestation { witness_id: "w1".to_string(), score: 0.7, witness_composite: 0.8 },
+            Attestation { witness_id: "w2".to_string(), score: 0.7, witness_composite: 0.8 },
+        ];
+        let json = serde_json::to_string(&atts).unwrap();
+
+        // f = 1 needs N >= 4; only 2 witnesses -> quorum fails.
+        let out = aggregate_consensus_impl(&json, 1, 0.05).unwrap();
+        let result: ConsensusResult = serde_json::from_str(&out).unwrap();
+        assert!(!result.quorum_met);
+    }
 }